@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rust_coinselect::{
+    select_coin_bnb, select_coin_knapsack, select_coin_srd, CoinSelectionOpt,
+    CoinSelectionOptBuilder, ExcessStrategy, FeeRate, OutputGroup,
+};
+
+/// The input set sizes every selector is benchmarked on.
+const SET_SIZES: [usize; 3] = [100, 1_000, 10_000];
+
+/// A wallet-like set of `size` P2WPKH inputs, from dust-adjacent to whole coins, with values
+/// spread over orders of magnitude. Seeded, so every run benchmarks the same set.
+fn utxo_set(size: usize) -> Vec<OutputGroup> {
+    let mut rng = StdRng::seed_from_u64(size as u64);
+    (0..size)
+        .map(|_| {
+            let value = 10u64.pow(rng.gen_range(3..9)) * rng.gen_range(1..10);
+            OutputGroup::single(value, 272, true)
+        })
+        .collect()
+}
+
+/// Options paying a quarter of the set's total value at 2 sat/vB.
+fn options_for(inputs: &[OutputGroup]) -> CoinSelectionOpt {
+    let total_value: u64 = inputs.iter().map(|input| input.value).sum();
+    CoinSelectionOptBuilder::new(total_value / 4, FeeRate::from_sat_per_kwu(500))
+        .long_term_feerate(FeeRate::from_sat_per_kwu(250))
+        .base_weight(440)
+        .drain_weight(124)
+        .drain_cost(130)
+        .cost_per_input(68)
+        .cost_per_output(62)
+        .min_drain_value(294)
+        .excess_strategy(ExcessStrategy::ToDrain)
+        .build()
+}
+
+fn bench_selectors(c: &mut Criterion) {
+    let mut group = c.benchmark_group("selection");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(10));
+    for size in SET_SIZES {
+        let inputs = utxo_set(size);
+        let options = options_for(&inputs);
+
+        // Criterion only measures time, so the search effort is reported once per set.
+        let mut rng = StdRng::seed_from_u64(0);
+        match select_coin_bnb(&inputs, options, &mut rng) {
+            Ok(selection) => println!(
+                "select_coin_bnb/{size}: {:?} after {} tries",
+                selection.chosen_algorithm, selection.iterations
+            ),
+            Err(err) => println!("select_coin_bnb/{size}: {err:?}"),
+        }
+
+        group.bench_with_input(BenchmarkId::new("bnb", size), &inputs, |b, inputs| {
+            let mut rng = StdRng::seed_from_u64(0);
+            b.iter(|| select_coin_bnb(inputs, options, &mut rng))
+        });
+        group.bench_with_input(BenchmarkId::new("knapsack", size), &inputs, |b, inputs| {
+            let mut rng = StdRng::seed_from_u64(0);
+            b.iter(|| select_coin_knapsack(inputs, options, &mut rng))
+        });
+        group.bench_with_input(BenchmarkId::new("srd", size), &inputs, |b, inputs| {
+            let mut rng = StdRng::seed_from_u64(0);
+            b.iter(|| select_coin_srd(inputs, options, &mut rng))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_selectors);
+criterion_main!(benches);
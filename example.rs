@@ -1,944 +0,0 @@
-#![allow(unused)]
-
-//! A blockchain-agnostic Rust Coinselection library
-
-use rand::{rngs::ThreadRng, seq::SliceRandom, Rng};
-use std::{option, vec};
-
-/// A [`OutputGroup`] represents an input candidate for Coinselection. This can either be a
-/// single UTXO, or a group of UTXOs that should be spent together.
-/// The library user is responsible for crafting this structure correctly. Incorrect representation of this
-/// structure will cause incorrect selection result.
-#[derive(Debug, Clone, Copy)]
-pub struct OutputGroup {
-    /// Total value of the UTXO(s) that this [`WeightedValue`] represents.
-    pub value: u64,
-    /// Total weight of including this/these UTXO(s).
-    /// `txin` fields: `prevout`, `nSequence`, `scriptSigLen`, `scriptSig`, `scriptWitnessLen`,
-    /// `scriptWitness` should all be included.
-    pub weight: u32,
-    /// The total number of inputs; so we can calculate extra `varint` weight due to `vin` length changes.
-    pub input_count: usize,
-    /// Whether this [`OutputGroup`] contains at least one segwit spend.
-    pub is_segwit: bool,
-    /// Relative Creation sequence for this group. Only used for FIFO selection. Specify None, if FIFO
-    /// selection is not required.
-    /// sequence numbers are arbitrary index only to denote relative age of utxo group among a set of groups.
-    /// To denote the oldest utxo group, give them a sequence number of Some(0).
-    pub creation_sequence: Option<u32>,
-}
-
-/// A set of Options that guides the CoinSelection algorithms. These are inputs specified by the
-/// user to perform coinselection to achieve a set a target parameters.
-#[derive(Debug, Clone, Copy)]
-pub struct CoinSelectionOpt {
-    /// The value we need to select.
-    pub target_value: u64,
-
-    /// The feerate we should try and achieve in sats per weight unit.
-    pub target_feerate: f32,
-    /// The feerate
-    pub long_term_feerate: Option<f32>, // TODO: Maybe out of scope? (waste)
-    /// The minimum absolute fee. I.e., needed for RBF.
-    pub min_absolute_fee: u64,
-
-    /// The weight of the template transaction, including fixed fields and outputs.
-    pub base_weight: u32,
-    /// Additional weight if we include the drain (change) output.
-    pub drain_weight: u32,
-
-    /// Weight of spending the drain (change) output in the future.
-    pub drain_cost: u64,
-
-    /// Estimate of cost of spending an input
-    pub cost_per_input: u64,
-
-    /// Estimate of cost of spending the output
-    pub cost_per_output: u64,
-
-    /// Minimum value allowed for a drain (change) output.
-    pub min_drain_value: u64,
-
-    /// Strategy to use the excess value other than fee and target
-    pub excess_strategy: ExcessStrategy,
-}
-
-/// Strategy to decide what to do with the excess amount.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum ExcessStrategy {
-    ToFee,
-    ToRecipient,
-    ToDrain,
-}
-
-/// Error Describing failure of a selection attempt, on any subset of inputs
-#[derive(Debug)]
-pub enum SelectionError {
-    InsufficientFunds,
-    NoSolutionFound,
-}
-
-/// Calculated waste for a specific selection.
-/// This is used to compare various selection algorithm and find the most
-/// optimizewd solution, represented by least [WasteMetric] value.
-#[derive(Debug)]
-pub struct WasteMetric(u64);
-
-/// The result of selection algorithm
-#[derive(Debug)]
-pub struct SelectionOutput {
-    /// The selected input indices, refers to the indices of the inputs Slice Reference
-    pub selected_inputs: Vec<usize>,
-    /// The waste amount, for the above inputs
-    pub waste: WasteMetric,
-}
-
-pub fn select_coin_bnb(
-    inputs: &[OutputGroup],
-    options: CoinSelectionOpt,
-    rng: &mut ThreadRng,
-) -> Result<SelectionOutput, SelectionError> {
-    println!("Starting select_coin_bnb with {} inputs", inputs.len());
-    println!("Target value: {}", options.target_value);
-
-    let mut selected_inputs: Vec<usize> = vec![];
-    const BNB_TRIES: u32 = 1_000_000;
-
-    let mut sorted_inputs: Vec<(usize, OutputGroup)> = inputs
-        .iter()
-        .enumerate()
-        .map(|(index, input)| (index, *input))
-        .collect();
-    sorted_inputs.sort_by_key(|(_, input)| std::cmp::Reverse(input.value));
-
-    println!(
-        "Sorted inputs: {:?}",
-        sorted_inputs
-            .iter()
-            .map(|(_, og)| og.value)
-            .collect::<Vec<_>>()
-    );
-
-    let bnb_selected_coin = bnb(
-        &sorted_inputs,
-        &mut selected_inputs,
-        0,
-        0,
-        BNB_TRIES,
-        &options,
-        rng,
-    );
-
-    println!("BnB result: {:?}", bnb_selected_coin);
-
-    match bnb_selected_coin {
-        Some(selected_coin) => {
-            let accumulated_value: u64 = selected_coin
-                .iter()
-                .fold(0, |acc, &i| acc + inputs[i].value);
-            let accumulated_weight: u32 = selected_coin
-                .iter()
-                .fold(0, |acc, &i| acc + inputs[i].weight);
-            let estimated_fee = 0;
-            let waste = calculate_waste(
-                inputs,
-                &selected_inputs,
-                &options,
-                accumulated_value,
-                accumulated_weight,
-                estimated_fee,
-            );
-            let selection_output = SelectionOutput {
-                selected_inputs: selected_coin,
-                waste: WasteMetric(waste),
-            };
-            println!(
-                "Selected inputs: {:?}, Total value: {}",
-                selection_output.selected_inputs, accumulated_value
-            );
-            Ok(selection_output)
-        }
-        None => {
-            println!("No solution found");
-            Err(SelectionError::NoSolutionFound)
-        }
-    }
-}
-
-fn bnb(
-    inputs_in_desc_value: &[(usize, OutputGroup)],
-    selected_inputs: &mut Vec<usize>,
-    acc_eff_value: u64,
-    depth: usize,
-    bnp_tries: u32,
-    options: &CoinSelectionOpt,
-    rng: &mut ThreadRng,
-) -> Option<Vec<usize>> {
-    println!(
-        "BnB: depth={}, acc_eff_value={}, selected_inputs={:?}",
-        depth, acc_eff_value, selected_inputs
-    );
-
-    let target_for_match = options.target_value
-        + calculate_fee(options.base_weight, options.target_feerate)
-        + options.cost_per_output;
-    let match_range = options.cost_per_input + options.cost_per_output;
-
-    println!(
-        "Target for match: {}, Match range: {}",
-        target_for_match, match_range
-    );
-
-    if acc_eff_value > target_for_match + match_range {
-        println!("Accumulated value too high, returning None");
-        return None;
-    }
-    if acc_eff_value >= target_for_match {
-        println!("Solution found: {:?}", selected_inputs);
-        return Some(selected_inputs.to_vec());
-    }
-    if bnp_tries == 0 || depth >= inputs_in_desc_value.len() {
-        println!("Reached max tries or depth, returning None");
-        return None;
-    }
-
-    if rng.gen_bool(0.5) {
-        // exploring the inclusion branch
-        let new_effective_values =
-            acc_eff_value + effective_value(&inputs_in_desc_value[depth].1, options.target_feerate);
-        selected_inputs.push(inputs_in_desc_value[depth].0);
-        println!("Including input at depth {}", depth);
-        let with_this = bnb(
-            inputs_in_desc_value,
-            selected_inputs,
-            new_effective_values,
-            depth + 1,
-            bnp_tries - 1,
-            options,
-            rng,
-        );
-        match with_this {
-            Some(_) => with_this,
-            None => {
-                selected_inputs.pop();
-                println!("Excluding input at depth {} after inclusion failed", depth);
-                bnb(
-                    inputs_in_desc_value,
-                    selected_inputs,
-                    acc_eff_value,
-                    depth + 1,
-                    bnp_tries - 1,
-                    options,
-                    rng,
-                )
-            }
-        }
-    } else {
-        println!("Excluding input at depth {}", depth);
-        let without_this = bnb(
-            inputs_in_desc_value,
-            selected_inputs,
-            acc_eff_value,
-            depth + 1,
-            bnp_tries - 2,
-            options,
-            rng,
-        );
-        match without_this {
-            Some(_) => without_this,
-            None => {
-                let new_effective_values = acc_eff_value
-                    + effective_value(&inputs_in_desc_value[depth].1, options.target_feerate);
-                selected_inputs.push(inputs_in_desc_value[depth].0);
-                println!("Including input at depth {} after exclusion failed", depth);
-                let with_this = bnb(
-                    inputs_in_desc_value,
-                    selected_inputs,
-                    new_effective_values,
-                    depth + 1,
-                    bnp_tries - 2,
-                    options,
-                    rng,
-                );
-                if with_this.is_none() {
-                    selected_inputs.pop();
-                }
-                with_this
-            }
-        }
-    }
-}
-/// Perform Coinselection via Knapsack solver.
-pub fn select_coin_knapsack(
-    inputs: &[OutputGroup],
-    options: CoinSelectionOpt,
-) -> Result<SelectionOutput, SelectionError> {
-    unimplemented!()
-}
-
-/// adjusted_target should be target value plus estimated fee
-/// smaller_coins is a slice of pair where the usize refers to the index of the OutputGroup in the inputs given
-/// smaller_coins should be sorted in descending order based on the value of the OutputGroup, and every OutputGroup value should be less than adjusted_target
-fn knap_sack(
-    adjusted_target: u64,
-    smaller_coins: &[(usize, OutputGroup)],
-) -> Result<SelectionOutput, SelectionError> {
-    unimplemented!()
-}
-
-/// Perform Coinselection via Lowest Larger algorithm.
-/// Return NoSolutionFound, if no solution exists.
-pub fn select_coin_lowestlarger(
-    inputs: &[OutputGroup],
-    options: CoinSelectionOpt,
-) -> Result<SelectionOutput, SelectionError> {
-    let mut accumulated_value: u64 = 0;
-    let mut accumulated_weight: u32 = 0;
-    let mut selected_inputs: Vec<usize> = Vec::new();
-    let mut estimated_fees: u64 = 0;
-    let target = options.target_value + options.min_drain_value;
-
-    let mut sorted_inputs: Vec<_> = inputs.iter().enumerate().collect();
-    sorted_inputs.sort_by_key(|(_, input)| effective_value(input, options.target_feerate));
-
-    let mut index = sorted_inputs.partition_point(|(_, input)| {
-        input.value <= (target + calculate_fee(input.weight, options.target_feerate))
-    });
-
-    for (idx, input) in sorted_inputs.iter().take(index).rev() {
-        accumulated_value += input.value;
-        accumulated_weight += input.weight;
-        estimated_fees = calculate_fee(accumulated_weight, options.target_feerate);
-        selected_inputs.push(*idx);
-
-        if accumulated_value >= (target + estimated_fees.max(options.min_absolute_fee)) {
-            break;
-        }
-    }
-
-    if accumulated_value < (target + estimated_fees.max(options.min_absolute_fee)) {
-        for (idx, input) in sorted_inputs.iter().skip(index) {
-            accumulated_value += input.value;
-            accumulated_weight += input.weight;
-            estimated_fees = calculate_fee(accumulated_weight, options.target_feerate);
-            selected_inputs.push(*idx);
-
-            if accumulated_value >= (target + estimated_fees.max(options.min_absolute_fee)) {
-                break;
-            }
-        }
-    }
-
-    if accumulated_value < (target + estimated_fees.max(options.min_absolute_fee)) {
-        Err(SelectionError::InsufficientFunds)
-    } else {
-        let waste: u64 = calculate_waste(
-            inputs,
-            &selected_inputs,
-            &options,
-            accumulated_value,
-            accumulated_weight,
-            estimated_fees,
-        );
-        Ok(SelectionOutput {
-            selected_inputs,
-            waste: WasteMetric(waste),
-        })
-    }
-}
-
-/// Perform Coinselection via First-In-First-Out algorithm.
-/// Return NoSolutionFound, if no solution exists.
-pub fn select_coin_fifo(
-    inputs: &[OutputGroup],
-    options: CoinSelectionOpt,
-) -> Result<SelectionOutput, SelectionError> {
-    let mut accumulated_value: u64 = 0;
-    let mut accumulated_weight: u32 = 0;
-    let mut selected_inputs: Vec<usize> = Vec::new();
-    let mut estimated_fees: u64 = 0;
-
-    // Sorting the inputs vector based on creation_sequence
-
-    let mut sorted_inputs: Vec<_> = inputs.iter().enumerate().collect();
-
-    sorted_inputs.sort_by_key(|(_, a)| a.creation_sequence);
-
-    for (index, inputs) in sorted_inputs {
-        estimated_fees = calculate_fee(accumulated_weight, options.target_feerate);
-        if accumulated_value
-            >= (options.target_value
-                + estimated_fees.max(options.min_absolute_fee)
-                + options.min_drain_value)
-        {
-            break;
-        }
-        accumulated_value += inputs.value;
-        accumulated_weight += inputs.weight;
-        selected_inputs.push(index);
-    }
-    if accumulated_value
-        < (options.target_value
-            + estimated_fees.max(options.min_absolute_fee)
-            + options.min_drain_value)
-    {
-        Err(SelectionError::InsufficientFunds)
-    } else {
-        let waste: u64 = calculate_waste(
-            inputs,
-            &selected_inputs,
-            &options,
-            accumulated_value,
-            accumulated_weight,
-            estimated_fees,
-        );
-        Ok(SelectionOutput {
-            selected_inputs,
-            waste: WasteMetric(waste),
-        })
-    }
-}
-
-/// Perform Coinselection via Single Random Draw.
-/// Return NoSolutionFound, if no solution exists.
-pub fn select_coin_srd(
-    inputs: &[OutputGroup],
-    options: CoinSelectionOpt,
-    rng: &mut ThreadRng,
-) -> Result<SelectionOutput, SelectionError> {
-    // Randomize the inputs order to simulate the random draw
-    // In out put we need to specify the indexes of the inputs in the given order
-    // So keep track of the indexes when randomiz ing the vec
-    let mut randomized_inputs: Vec<_> = inputs.iter().enumerate().collect();
-
-    // Randomize the inputs order to simulate the random draw
-    randomized_inputs.shuffle(rng);
-
-    let mut accumulated_value = 0;
-    let mut selected_inputs = Vec::new();
-    let mut accumulated_weight = 0;
-    let mut estimated_fee = 0;
-    let mut input_counts = 0;
-
-    let necessary_target = options.target_value
-        + options.min_drain_value
-        + calculate_fee(options.base_weight, options.target_feerate);
-
-    for (index, input) in randomized_inputs {
-        selected_inputs.push(index);
-        accumulated_value += input.value;
-        accumulated_weight += input.weight;
-        input_counts += input.input_count;
-
-        estimated_fee = calculate_fee(accumulated_weight, options.target_feerate);
-
-        if accumulated_value
-            >= options.target_value
-                + options.min_drain_value
-                + estimated_fee.max(options.min_absolute_fee)
-        {
-            break;
-        }
-    }
-
-    if accumulated_value
-        < options.target_value
-            + options.min_drain_value
-            + estimated_fee.max(options.min_absolute_fee)
-    {
-        return Err(SelectionError::InsufficientFunds);
-    }
-    // accumulated_weight += weightof(input_counts)?? TODO
-    let waste = calculate_waste(
-        inputs,
-        &selected_inputs,
-        &options,
-        accumulated_value,
-        accumulated_weight,
-        estimated_fee,
-    );
-
-    Ok(SelectionOutput {
-        selected_inputs,
-        waste: WasteMetric(waste),
-    })
-}
-
-/// The Global Coinselection API that performs all the algorithms and proudeces result with least [WasteMetric].
-/// At least one selection solution should be found.
-pub fn select_coin(
-    inputs: &[OutputGroup],
-    options: CoinSelectionOpt,
-) -> Result<SelectionOutput, SelectionError> {
-    unimplemented!()
-}
-
-#[inline]
-fn calculate_waste(
-    inputs: &[OutputGroup],
-    selected_inputs: &[usize],
-    options: &CoinSelectionOpt,
-    accumulated_value: u64,
-    accumulated_weight: u32,
-    estimated_fee: u64,
-) -> u64 {
-    let mut waste: u64 = 0;
-
-    if let Some(long_term_feerate) = options.long_term_feerate {
-        waste += (estimated_fee as f32
-            - selected_inputs.len() as f32 * long_term_feerate * accumulated_weight as f32)
-            .ceil() as u64;
-    }
-
-    if options.excess_strategy != ExcessStrategy::ToDrain {
-        waste += accumulated_value - options.target_value - estimated_fee;
-    } else {
-        waste += options.drain_cost;
-    }
-
-    waste
-}
-
-#[inline]
-fn calculate_fee(weight: u32, rate: f32) -> u64 {
-    (weight as f32 * rate).ceil() as u64
-}
-
-/// Returns the effective value which is the actual value minus the estimated fee of the OutputGroup
-#[inline]
-fn effective_value(output: &OutputGroup, feerate: f32) -> u64 {
-    output
-        .value
-        .saturating_sub(calculate_fee(output.weight, feerate))
-}
-
-fn generate_random_bool(rng: &mut ThreadRng) -> bool {
-    // Generate a random boolean value
-    rng.gen()
-}
-
-#[cfg(test)]
-mod test {
-
-    use rand::thread_rng;
-
-    use super::*;
-
-    fn setup_basic_output_groups() -> Vec<OutputGroup> {
-        vec![
-            OutputGroup {
-                value: 1000,
-                weight: 100,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: None,
-            },
-            OutputGroup {
-                value: 2000,
-                weight: 200,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: None,
-            },
-            OutputGroup {
-                value: 3000,
-                weight: 300,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: None,
-            },
-        ]
-    }
-    fn setup_output_groups_withsequence() -> Vec<OutputGroup> {
-        vec![
-            OutputGroup {
-                value: 1000,
-                weight: 100,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: Some(1),
-            },
-            OutputGroup {
-                value: 2000,
-                weight: 200,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: Some(5000),
-            },
-            OutputGroup {
-                value: 3000,
-                weight: 300,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: Some(1001),
-            },
-        ]
-    }
-    fn setup_lowestlarger_output_groups() -> Vec<OutputGroup> {
-        vec![
-            OutputGroup {
-                value: 100,
-                weight: 100,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: None,
-            },
-            OutputGroup {
-                value: 1500,
-                weight: 200,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: None,
-            },
-            OutputGroup {
-                value: 3400,
-                weight: 300,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: None,
-            },
-            OutputGroup {
-                value: 2200,
-                weight: 150,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: None,
-            },
-            OutputGroup {
-                value: 1190,
-                weight: 200,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: None,
-            },
-            OutputGroup {
-                value: 3300,
-                weight: 100,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: None,
-            },
-            OutputGroup {
-                value: 1000,
-                weight: 190,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: None,
-            },
-            OutputGroup {
-                value: 2000,
-                weight: 210,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: None,
-            },
-            OutputGroup {
-                value: 3000,
-                weight: 300,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: None,
-            },
-            OutputGroup {
-                value: 2250,
-                weight: 250,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: None,
-            },
-            OutputGroup {
-                value: 190,
-                weight: 220,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: None,
-            },
-            OutputGroup {
-                value: 1750,
-                weight: 170,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: None,
-            },
-        ]
-    }
-
-    fn bnb_setup_options(target_value: u64) -> CoinSelectionOpt {
-        CoinSelectionOpt {
-            target_value,
-            target_feerate: 0.5, // Simplified feerate
-            long_term_feerate: None,
-            min_absolute_fee: 0,
-            base_weight: 10,
-            drain_weight: 50,
-            drain_cost: 10,
-            cost_per_input: 20,
-            cost_per_output: 10,
-            min_drain_value: 500,
-            excess_strategy: ExcessStrategy::ToDrain,
-        }
-    }
-
-    #[test]
-    fn test_bnb_basic() {
-        // Perform BNB selection of set of test values.
-        let values = [
-            OutputGroup {
-                value: 2000,
-                weight: 200,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: Some(1),
-            },
-            OutputGroup {
-                value: 5000000,
-                weight: 200,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: Some(5000),
-            },
-            OutputGroup {
-                value: 9000000,
-                weight: 300,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: Some(1001),
-            },
-            OutputGroup {
-                value: 270,
-                weight: 10,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: Some(1000),
-            },
-        ];
-        let opt = bnb_setup_options(14000000);
-        let ans = select_coin_bnb(&values, opt, &mut rand::thread_rng());
-        assert!(ans.is_ok());
-        assert!(!ans.unwrap().selected_inputs.contains(&0));
-        // as 10000000 should not be included in the selection
-    }
-
-    #[test]
-    fn test_bnb_exact_one_solution() {
-        // Define the test values
-        let values = [
-            OutputGroup {
-                value: 55000,
-                weight: 500,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: None,
-            },
-            OutputGroup {
-                value: 400,
-                weight: 200,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: None,
-            },
-            OutputGroup {
-                value: 40000,
-                weight: 300,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: None,
-            },
-            OutputGroup {
-                value: 25000,
-                weight: 100,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: None,
-            },
-            OutputGroup {
-                value: 35000,
-                weight: 150,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: None,
-            },
-            OutputGroup {
-                value: 600,
-                weight: 250,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: None,
-            },
-            OutputGroup {
-                value: 30000,
-                weight: 120,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: None,
-            },
-            OutputGroup {
-                value: 5000,
-                weight: 50,
-                input_count: 1,
-                is_segwit: false,
-                creation_sequence: None,
-            },
-        ];
-
-        // Adjust the target value to ensure it tests for multiple valid solutions
-        let opt = bnb_setup_options(5730);
-        let ans = select_coin_bnb(&values, opt, &mut rand::thread_rng());
-        if let Ok(selection_output) = ans {
-            let expected_solution = vec![7, 5, 1];
-            assert_eq!(
-                selection_output.selected_inputs, expected_solution,
-                "Expected solution {:?}, but got {:?}",
-                expected_solution, selection_output.selected_inputs
-            );
-        } else {
-            panic!("Failed to find a solution");
-        }
-    }
-
-    #[test]
-    fn test_bnb_multiple_solutions() {
-        // Define the test values
-        let values = [
-            OutputGroup { value: 55000, weight: 500, input_count: 1, is_segwit: false, creation_sequence: None },
-            OutputGroup { value: 40000, weight: 200, input_count: 1, is_segwit: false, creation_sequence: None },
-            OutputGroup { value: 40000, weight: 300, input_count: 1, is_segwit: false, creation_sequence: None },
-            OutputGroup { value: 25000, weight: 100, input_count: 1, is_segwit: false, creation_sequence: None },
-            OutputGroup { value: 35000, weight: 150, input_count: 1, is_segwit: false, creation_sequence: None },
-            OutputGroup { value: 60000, weight: 250, input_count: 1, is_segwit: false, creation_sequence: None },
-            OutputGroup { value: 30000, weight: 120, input_count: 1, is_segwit: false, creation_sequence: None },
-            OutputGroup { value: 5000, weight: 50, input_count: 1, is_segwit: false, creation_sequence: None },
-        ];
-
-        // Adjust the target value to ensure it's achievable
-        let opt = bnb_setup_options(93000);
-
-        // Define the valid combinations
-        let valid_combinations = vec![vec![0, 1], vec![0, 2], vec![1, 3, 6], vec![2, 3, 6]];
-        let mut found_solutions = Vec::new();
-        let mut rng = rand::thread_rng();
-
-        println!("Starting BnB selection with target value: {}", opt.target_value);
-
-        // Run the BnB selection algorithm multiple times to find different solutions
-        for i in 0..1000 {
-            let ans = select_coin_bnb(&values, opt, &mut rng);
-            println!("Iteration {}: Result = {:?}", i, ans);
-
-            if let Ok(selection_output) = ans {
-                let selected_inputs = selection_output.selected_inputs;
-                let total_value: u64 = selected_inputs.iter().map(|&i| values[i].value).sum();
-                println!("Selected inputs: {:?}, Total value: {}", selected_inputs, total_value);
-
-                // Check if the selected inputs match any of the valid combinations
-                if valid_combinations.contains(&selected_inputs) && !found_solutions.contains(&selected_inputs) {
-                    found_solutions.push(selected_inputs.clone());
-                    println!("Found new solution: {:?}", selected_inputs);
-                }
-            }
-
-            // Print progress every 100 iterations
-            if (i + 1) % 100 == 0 {
-                println!("Completed {} iterations. Current found solutions: {:?}", i + 1, found_solutions);
-            }
-
-            // Break early if all solutions are found
-            if found_solutions.len() == valid_combinations.len() {
-                println!("All solutions found after {} iterations", i + 1);
-                break;
-            }
-        }
-
-        // Ensure that all valid combinations are found
-        assert!(
-            found_solutions.len() == valid_combinations.len(),
-            "Expected all valid combinations, but found fewer: found {}, expected {}",
-            found_solutions.len(),
-            valid_combinations.len()
-        );
-
-        println!("Final found solutions: {:?}", found_solutions);
-    }
-
-    #[test]
-    fn test_bnb_no_solution() {
-        let inputs = setup_basic_output_groups();
-        let total_input_value: u64 = inputs.iter().map(|input| input.value).sum();
-        let impossible_target = total_input_value + 1;
-        let options = bnb_setup_options(impossible_target);
-
-        let result = select_coin_bnb(&inputs, options, &mut rand::thread_rng());
-
-        assert!(
-            matches!(result, Err(SelectionError::NoSolutionFound)),
-            "Expected NoSolutionFound error, got {:?}",
-            result
-        );
-    }
-
-    fn test_successful_selection() {
-        let mut inputs = setup_basic_output_groups();
-        let mut options = bnb_setup_options(2500);
-        let mut result = select_coin_srd(&inputs, options, &mut rand::thread_rng());
-        assert!(result.is_ok());
-        let mut selection_output = result.unwrap();
-        assert!(!selection_output.selected_inputs.is_empty());
-
-        inputs = setup_output_groups_withsequence();
-        options = bnb_setup_options(500);
-        result = select_coin_fifo(&inputs, options);
-        assert!(result.is_ok());
-        selection_output = result.unwrap();
-        assert!(!selection_output.selected_inputs.is_empty());
-    }
-
-    fn test_insufficient_funds() {
-        let inputs = setup_basic_output_groups();
-        let options = bnb_setup_options(7000); // Set a target value higher than the sum of all inputs
-        let result = select_coin_srd(&inputs, options, &mut rand::thread_rng());
-        assert!(matches!(result, Err(SelectionError::InsufficientFunds)));
-    }
-
-    #[test]
-    fn test_srd() {
-        test_successful_selection();
-        test_insufficient_funds();
-    }
-
-    #[test]
-    fn test_knapsack() {
-        // Perform Knapsack selection of set of test values.
-    }
-
-    #[test]
-    fn test_bnb() {
-        test_bnb_basic();
-        test_bnb_exact_one_solution();
-        test_bnb_multiple_solutions();
-        test_bnb_no_solution();
-    }
-
-    #[test]
-    fn test_fifo() {
-        test_successful_selection();
-        test_insufficient_funds();
-    }
-
-    #[test]
-    fn test_lowestlarger_successful() {
-        let mut inputs = setup_lowestlarger_output_groups();
-        let mut options = bnb_setup_options(20000);
-        let result = select_coin_lowestlarger(&inputs, options);
-        assert!(result.is_ok());
-        let selection_output = result.unwrap();
-        assert!(!selection_output.selected_inputs.is_empty());
-    }
-
-    #[test]
-    fn test_lowestlarger_insufficient() {
-        let mut inputs = setup_lowestlarger_output_groups();
-        let mut options = bnb_setup_options(40000);
-        let result = select_coin_lowestlarger(&inputs, options);
-        assert!(matches!(result, Err(SelectionError::InsufficientFunds)));
-    }
-}
@@ -0,0 +1,82 @@
+use alloc::vec::Vec;
+
+use crate::{
+    types::{Algorithm, CoinSelectionOpt, OutputGroup, SelectionError, SelectionOutput},
+    utils::{
+        accumulate, covers_target, finalize_selection, is_dust, meets_min_efficiency,
+        required_value, spendable_with_change, transaction_fee,
+    },
+};
+
+/// Perform Coinselection by spending the largest input at or below the target, then topping up
+/// with the smallest input that covers the rest on its own, or failing that the largest remaining
+/// input, until the target, fee and required change are covered.
+/// This tends to produce small selections with little change.
+/// Return InsufficientFunds, if the inputs cannot cover the target.
+#[must_use = "the selection result tells which inputs to spend"]
+pub fn select_coin_anchor_topup(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+) -> Result<SelectionOutput, SelectionError> {
+    options.validate()?;
+    let (mut accumulated_value, mut accumulated_weight) = options.anchor.unwrap_or((0, 0));
+    let mut selected_inputs: Vec<usize> = Vec::new();
+    let mut has_segwit = false;
+
+    let mut sorted_inputs: Vec<_> = inputs
+        .iter()
+        .enumerate()
+        .filter(|(_, input)| spendable_with_change(input, &options))
+        .filter(|(_, input)| meets_min_efficiency(input, &options))
+        .filter(|(_, input)| !is_dust(input, &options))
+        .collect();
+    sorted_inputs.sort_by_key(|(_, input)| input.value);
+
+    let closest_smaller =
+        sorted_inputs.partition_point(|(_, input)| input.value <= options.target_value);
+    if closest_smaller > 0 {
+        let (index, input) = sorted_inputs.remove(closest_smaller - 1);
+        (accumulated_value, accumulated_weight) = accumulate(
+            (accumulated_value, accumulated_weight),
+            input,
+            &mut has_segwit,
+        )?;
+        selected_inputs.push(index);
+    }
+
+    while !covers_target(&options, accumulated_value, accumulated_weight) {
+        let closing = sorted_inputs.iter().position(|(_, input)| {
+            covers_target(
+                &options,
+                accumulated_value.saturating_add(input.value),
+                accumulated_weight.saturating_add(input.weight),
+            )
+        });
+        let Some((index, input)) = (match closing {
+            Some(position) => Some(sorted_inputs.remove(position)),
+            None => sorted_inputs.pop(),
+        }) else {
+            break;
+        };
+        (accumulated_value, accumulated_weight) = accumulate(
+            (accumulated_value, accumulated_weight),
+            input,
+            &mut has_segwit,
+        )?;
+        selected_inputs.push(index);
+    }
+
+    if accumulated_value < required_value(&options, transaction_fee(&options, accumulated_weight)?)?
+    {
+        Err(SelectionError::InsufficientFunds)
+    } else {
+        finalize_selection(
+            inputs,
+            selected_inputs,
+            &options,
+            accumulated_value,
+            accumulated_weight,
+            Algorithm::AnchorTopup,
+        )
+    }
+}
@@ -0,0 +1,867 @@
+use alloc::{collections::BTreeSet, vec, vec::Vec};
+use core::ops::ControlFlow;
+#[cfg(feature = "rayon")]
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use rand::Rng;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use super::{select_coin_with_forced, srd::srd};
+#[cfg(feature = "rayon")]
+use crate::types::SeedSource;
+use crate::{
+    types::{
+        Algorithm, CoinSelectionOpt, ExcessStrategy, FeeRate, InputIndex, OutputGroup,
+        SelectionError, SelectionOutput,
+    },
+    utils::{
+        anchor_effective_value, buffered_fee, calculate_fee, compare_selections,
+        finalize_selection, group_effective_value, group_effective_value_at, is_dust, is_forbidden,
+        meets_min_efficiency, selection_totals, SEGWIT_MARKER_WEIGHT,
+    },
+};
+
+/// Parameters derived from [`CoinSelectionOpt`] that decide when a branch of the
+/// Branch and Bound search is a match.
+/// A selection matches when its accumulated effective value lies within
+/// `target_for_match..=target_for_match + match_range`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MatchParameters {
+    target_for_match: u64,
+    match_range: u64,
+    target_feerate: FeeRate,
+    cost_per_input: u64,
+    max_inputs: Option<usize>,
+}
+
+impl MatchParameters {
+    /// Compute the match parameters for the given options.
+    /// Return Overflow, if the target or the range does not fit in a `u64`.
+    pub fn from_options(options: &CoinSelectionOpt) -> Result<MatchParameters, SelectionError> {
+        let base_fee = base_fee(options);
+        let outputs_cost = u64::try_from(options.num_outputs)
+            .ok()
+            .and_then(|num_outputs| options.cost_per_output.checked_mul(num_outputs))
+            .ok_or(SelectionError::Overflow)?;
+        let target_for_match = options
+            .target_value
+            .checked_add(base_fee)
+            .and_then(|target| target.checked_add(outputs_cost))
+            .ok_or(SelectionError::Overflow)?;
+        let match_range = options
+            .cost_per_input
+            .checked_add(options.cost_per_output)
+            .ok_or(SelectionError::Overflow)?;
+        // The upper bound of the match window must be representable as well, and below
+        // `u64::MAX` so that saturated sums of effective values fall outside the window.
+        target_for_match
+            .checked_add(match_range)
+            .filter(|&upper_bound| upper_bound < u64::MAX)
+            .ok_or(SelectionError::Overflow)?;
+        Ok(MatchParameters {
+            target_for_match,
+            match_range,
+            target_feerate: options.target_feerate,
+            cost_per_input: options.cost_per_input,
+            max_inputs: options.max_inputs,
+        })
+    }
+
+    /// The minimum accumulated effective value for a selection to match.
+    pub fn target_for_match(&self) -> u64 {
+        self.target_for_match
+    }
+
+    /// The allowed overshoot above [`MatchParameters::target_for_match`].
+    pub fn match_range(&self) -> u64 {
+        self.match_range
+    }
+
+    /// The feerate used to compute the effective value of the inputs.
+    pub fn target_feerate(&self) -> FeeRate {
+        self.target_feerate
+    }
+
+    /// The most inputs a match may spend, see [`CoinSelectionOpt::max_inputs`].
+    pub fn max_inputs(&self) -> Option<usize> {
+        self.max_inputs
+    }
+}
+
+/// The fee for the [`SEGWIT_MARKER_WEIGHT`], paid once by a selection spending a segwit input,
+/// which the effective values of the inputs leave out.
+fn segwit_marker_fee(feerate: FeeRate) -> u64 {
+    calculate_fee(SEGWIT_MARKER_WEIGHT, feerate)
+}
+
+/// Whether the accumulated effective value of `selected_inputs`, the anchor included, lies
+/// within the match window, so spending them leaves no change.
+pub(crate) fn in_match_window(
+    inputs: &[OutputGroup],
+    selected_inputs: &[usize],
+    options: &CoinSelectionOpt,
+    match_parameters: &MatchParameters,
+) -> bool {
+    let marker_fee = segwit_marker_fee(options.target_feerate);
+    let (acc_eff_value, _) = selected_inputs.iter().fold(
+        (anchor_effective_value(options), false),
+        |(acc_eff_value, marker_paid), &index| {
+            let input = &inputs[index];
+            let effective_value = group_effective_value(input, options).unwrap_or(0);
+            (
+                include_effective_value(
+                    acc_eff_value,
+                    effective_value,
+                    input.is_segwit && !marker_paid,
+                    marker_fee,
+                ),
+                marker_paid || input.is_segwit,
+            )
+        },
+    );
+    acc_eff_value >= match_parameters.target_for_match
+        && acc_eff_value <= match_parameters.target_for_match + match_parameters.match_range
+}
+
+/// `acc_eff_value` with an input of `effective_value` included, less the `marker_fee` if the input
+/// `pays_marker` as the first segwit input. A saturated sum stays saturated, above the match window.
+fn include_effective_value(
+    acc_eff_value: u64,
+    effective_value: u64,
+    pays_marker: bool,
+    marker_fee: u64,
+) -> u64 {
+    match acc_eff_value.checked_add(effective_value) {
+        Some(acc_eff_value) if pays_marker => acc_eff_value.saturating_sub(marker_fee),
+        Some(acc_eff_value) => acc_eff_value,
+        None => u64::MAX,
+    }
+}
+
+/// The fee for `base_weight` on top of the inputs' effective values.
+/// The effective values pay for the inputs, so topping it up to `min_absolute_fee` guarantees the
+/// transaction pays at least that much.
+fn base_fee(options: &CoinSelectionOpt) -> u64 {
+    buffered_fee(
+        options,
+        calculate_fee(options.base_weight, options.target_feerate),
+    )
+    .max(options.min_absolute_fee)
+}
+
+/// Perform Coinselection via Branch And Bound algorithm.
+/// Returns the first match the randomized search finds, which need not be the least waste
+/// one. Downstream code relies on this, so smarter searches are separate functions or flags.
+/// Return InsufficientFunds without searching, if all inputs worth spending together cannot pay
+/// the target and the fee.
+#[must_use = "the selection result tells which inputs to spend"]
+pub fn select_coin_bnb(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+    rng: &mut impl Rng,
+) -> Result<SelectionOutput, SelectionError> {
+    options.validate()?;
+    select_coin_bnb_with_forbidden_pairs(inputs, options, &[], rng)
+}
+
+/// Perform Coinselection via Branch And Bound algorithm, never spending both inputs of any
+/// of the `forbidden_pairs` together (e.g. to avoid linking two identities).
+/// Branches including both members of a pair are pruned, and the SRD fallback skips them too.
+#[must_use = "the selection result tells which inputs to spend"]
+pub fn select_coin_bnb_with_forbidden_pairs(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+    forbidden_pairs: &[(InputIndex, InputIndex)],
+    rng: &mut impl Rng,
+) -> Result<SelectionOutput, SelectionError> {
+    options.validate()?;
+    match bnb_changeless(inputs, options, forbidden_pairs, rng) {
+        Err(SelectionError::NoSolutionFound | SelectionError::SearchBudgetExhausted) => {
+            srd(inputs, options, forbidden_pairs, rng)
+        }
+        result => result,
+    }
+}
+
+/// Perform Coinselection via Branch And Bound algorithm, with the match target also paying the
+/// fee for `drain_weight` while the excess goes to a drain output, so matches land in a window
+/// consistent with producing a change output. Only the match window moves: the SRD fallback
+/// already pays for the drain output it creates.
+/// With any other [`ExcessStrategy`] this is [`select_coin_bnb`].
+#[must_use = "the selection result tells which inputs to spend"]
+pub fn select_coin_bnb_with_drain(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+    rng: &mut impl Rng,
+) -> Result<SelectionOutput, SelectionError> {
+    options.validate()?;
+    let match_parameters = MatchParameters::from_options(&with_drain_weight(&options)?)?;
+    match bnb_changeless_in_window(inputs, options, &match_parameters, &[], rng) {
+        Err(SelectionError::NoSolutionFound | SelectionError::SearchBudgetExhausted) => {
+            srd(inputs, options, &[], rng)
+        }
+        result => result,
+    }
+}
+
+/// Perform Coinselection via Branch And Bound algorithm, always spending the `must_include`
+/// inputs, e.g. to consolidate a UTXO or to spend a time-locked one. They are folded into the
+/// anchor like [`select_coin_with_forced`] does, and the search tops up from the other inputs.
+/// Only a changeless match is returned: unlike [`select_coin_bnb`], there is no SRD fallback.
+/// Return NoSolutionFound, if the mandatory inputs alone overshoot the match window or the whole
+/// tree holds no match, and SearchBudgetExhausted, if every attempt ran out of tries.
+#[must_use = "the selection result tells which inputs to spend"]
+pub fn select_coin_bnb_with_preselected(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+    must_include: &[InputIndex],
+    rng: &mut impl Rng,
+) -> Result<SelectionOutput, SelectionError> {
+    options.validate()?;
+    let match_parameters = MatchParameters::from_options(&options)?;
+    let preselected_options = CoinSelectionOpt {
+        anchor: Some(selection_totals(inputs, must_include, &options)?),
+        ..options
+    };
+    if anchor_effective_value(&preselected_options)
+        > match_parameters.target_for_match + match_parameters.match_range
+    {
+        return Err(SelectionError::NoSolutionFound);
+    }
+    select_coin_with_forced(inputs, options, must_include, |inputs, options| {
+        select_coin_bnb_changeless(inputs, options, rng)
+    })
+}
+
+/// `options` with `drain_weight` counted in `base_weight` while the excess goes to a drain.
+/// Return Overflow, if the weights do not fit in a `u32`.
+pub(crate) fn with_drain_weight(
+    options: &CoinSelectionOpt,
+) -> Result<CoinSelectionOpt, SelectionError> {
+    if options.excess_strategy != ExcessStrategy::ToDrain {
+        return Ok(*options);
+    }
+    let base_weight = options
+        .base_weight
+        .checked_add(options.drain_weight)
+        .ok_or(SelectionError::Overflow)?;
+    Ok(CoinSelectionOpt {
+        base_weight,
+        ..*options
+    })
+}
+
+/// Default search budget of a single Branch and Bound attempt.
+pub(crate) const BNB_TRIES: u32 = 1_000_000;
+
+/// Search budget of a single Branch and Bound attempt, see [`CoinSelectionOpt::bnb_tries`].
+#[inline]
+pub(crate) fn bnb_budget(options: &CoinSelectionOpt) -> u32 {
+    options.bnb_tries.unwrap_or(BNB_TRIES)
+}
+
+/// Number of extra Branch and Bound attempts made by [`select_coin_bnb_changeless`].
+/// The search branches randomly, so a new attempt may find a changeless match a previous
+/// attempt missed.
+const BNB_RETRIES: usize = 3;
+
+/// Perform Branch and Bound without falling back to change-producing strategies, retrying the
+/// search up to [`BNB_RETRIES`] times while it runs out of tries.
+/// Return NoSolutionFound, if an attempt explored the whole tree without a match, and
+/// SearchBudgetExhausted, if every attempt ran out of tries.
+pub(crate) fn select_coin_bnb_changeless<R: Rng>(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+    rng: &mut R,
+) -> Result<SelectionOutput, SelectionError> {
+    for _ in 0..BNB_RETRIES {
+        match bnb_changeless(inputs, options, &[], rng) {
+            Err(SelectionError::SearchBudgetExhausted) => continue,
+            result => return result,
+        }
+    }
+    bnb_changeless(inputs, options, &[], rng)
+}
+
+/// Perform Coinselection via Branch and Bound the way Bitcoin Core does: always exploring the
+/// inclusion branch first, over the inputs in descending value order, and returning the least
+/// waste of all matches found within the search budget.
+/// Unlike [`select_coin_bnb`], which flips a coin at every branch and returns its first match,
+/// the result only depends on the inputs and options, and there is no SRD fallback.
+/// Return NoSolutionFound, if the whole tree holds no changeless match, and
+/// SearchBudgetExhausted, if the search ran out of tries before finding one.
+#[must_use = "the selection result tells which inputs to spend"]
+pub fn select_coin_bnb_deterministic(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+) -> Result<SelectionOutput, SelectionError> {
+    options.validate()?;
+    let sorted_inputs = bnb_candidates(inputs, &options);
+    let match_parameters = MatchParameters::from_options(&options)?;
+    let mut bnb_tries = bnb_budget(&options);
+    let mut error = SelectionError::NoSolutionFound;
+    let mut best: Option<SelectionOutput> = None;
+    let exhausted = bnb_search(
+        &sorted_inputs,
+        &mut Vec::new(),
+        anchor_effective_value(&options),
+        0,
+        &mut bnb_tries,
+        &match_parameters,
+        &[],
+        true,
+        || true,
+        |selected_inputs| {
+            match finalize_match(inputs, selected_inputs.to_vec(), &options) {
+                Ok(selection) => {
+                    if best.as_ref().is_none_or(|best| {
+                        compare_selections(inputs, &selection, best, &options).is_lt()
+                    }) {
+                        best = Some(selection);
+                    }
+                }
+                Err(err) => error = err,
+            }
+            ControlFlow::Continue(())
+        },
+    );
+    if exhausted && best.is_none() {
+        error = SelectionError::SearchBudgetExhausted;
+    }
+    let mut best = best.ok_or(error)?;
+    best.iterations = bnb_budget(&options) - bnb_tries;
+    Ok(best)
+}
+
+/// Every distinct changeless match the Branch and Bound traversal of
+/// [`select_coin_bnb_deterministic`] finds within the search budget, least waste first, e.g. to
+/// present alternatives to [`select_coin_bnb`]'s pick. Each set of inputs is yielded once.
+/// The search runs before the first item is yielded, since ordering by waste needs every match;
+/// take the first item for the least waste one. Yields nothing if the options are invalid.
+pub fn bnb_solutions(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+) -> impl Iterator<Item = SelectionOutput> {
+    let mut solutions: Vec<SelectionOutput> = Vec::new();
+    let match_parameters = options
+        .validate()
+        .and_then(|()| MatchParameters::from_options(&options));
+    if let Ok(match_parameters) = match_parameters {
+        let sorted_inputs = bnb_candidates(inputs, &options);
+        let mut bnb_tries = bnb_budget(&options);
+        let mut seen: BTreeSet<Vec<usize>> = BTreeSet::new();
+        bnb_search(
+            &sorted_inputs,
+            &mut Vec::new(),
+            anchor_effective_value(&options),
+            0,
+            &mut bnb_tries,
+            &match_parameters,
+            &[],
+            true,
+            || true,
+            |selected_inputs| {
+                let mut input_set = selected_inputs.to_vec();
+                input_set.sort_unstable();
+                if seen.insert(input_set) {
+                    if let Ok(selection) =
+                        finalize_match(inputs, selected_inputs.to_vec(), &options)
+                    {
+                        solutions.push(selection);
+                    }
+                }
+                ControlFlow::Continue(())
+            },
+        );
+        solutions.sort_by(|a, b| compare_selections(inputs, a, b, &options));
+    }
+    solutions.into_iter()
+}
+
+/// Perform Branch and Bound on `threads` independent searches in parallel, each restarting like
+/// [`select_coin_bnb_changeless`] with the RNG of `seed` for its thread index, so `threads == 1`
+/// reproduces a single-threaded run with [`SeedSource::rng`]. A `threads` of 0 runs one search.
+/// The first match stops the other threads from starting new attempts; among the matches found,
+/// the least waste wins. Return NoSolutionFound, if an attempt explored the whole tree without a
+/// match, and SearchBudgetExhausted, if every attempt ran out of tries.
+#[cfg(feature = "rayon")]
+#[must_use = "the selection result tells which inputs to spend"]
+pub fn select_coin_bnb_parallel(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+    threads: usize,
+    seed: SeedSource,
+) -> Result<SelectionOutput, SelectionError> {
+    options.validate()?;
+    let found = AtomicBool::new(false);
+    let matches: Vec<Result<SelectionOutput, SelectionError>> = (0..threads.max(1) as u64)
+        .into_par_iter()
+        .map(|thread| {
+            let mut rng = seed.rng_for_stream(thread);
+            let mut result = Err(SelectionError::SearchBudgetExhausted);
+            for _ in 0..=BNB_RETRIES {
+                if found.load(Ordering::Relaxed) {
+                    break;
+                }
+                result = bnb_changeless(inputs, options, &[], &mut rng);
+                match result {
+                    Ok(_) => {
+                        found.store(true, Ordering::Relaxed);
+                        break;
+                    }
+                    Err(SelectionError::SearchBudgetExhausted) => {}
+                    Err(_) => break,
+                }
+            }
+            result
+        })
+        .collect();
+
+    let mut best: Option<SelectionOutput> = None;
+    let mut error = SelectionError::SearchBudgetExhausted;
+    for selection in matches {
+        let selection = match selection {
+            Ok(selection) => selection,
+            Err(SelectionError::SearchBudgetExhausted) => continue,
+            Err(SelectionError::NoSolutionFound) => {
+                error = SelectionError::NoSolutionFound;
+                continue;
+            }
+            Err(err) => return Err(err),
+        };
+        if best
+            .as_ref()
+            .is_none_or(|best| compare_selections(inputs, &selection, best, &options).is_lt())
+        {
+            best = Some(selection);
+        }
+    }
+    best.ok_or(error)
+}
+
+/// A single Branch and Bound search.
+/// Return InsufficientFunds, if all inputs worth spending cannot pay the target and the fee, and
+/// NoSolutionFound or SearchBudgetExhausted, if the search found no match, see [`bnb`].
+pub(crate) fn bnb_changeless<R: Rng>(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+    forbidden_pairs: &[(InputIndex, InputIndex)],
+    rng: &mut R,
+) -> Result<SelectionOutput, SelectionError> {
+    let match_parameters = MatchParameters::from_options(&options)?;
+    bnb_changeless_in_window(inputs, options, &match_parameters, forbidden_pairs, rng)
+}
+
+/// [`bnb_changeless`] with the match window given by `match_parameters` rather than `options`.
+fn bnb_changeless_in_window<R: Rng>(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+    match_parameters: &MatchParameters,
+    forbidden_pairs: &[(InputIndex, InputIndex)],
+    rng: &mut R,
+) -> Result<SelectionOutput, SelectionError> {
+    let mut bnb_tries = bnb_budget(&options);
+    let mut selection = first_changeless_match(
+        inputs,
+        &options,
+        match_parameters,
+        forbidden_pairs,
+        &mut bnb_tries,
+        rng,
+        |selected_inputs| finalize_match(inputs, selected_inputs.to_vec(), &options),
+    )?;
+    selection.iterations = bnb_budget(&options) - bnb_tries;
+    Ok(selection)
+}
+
+/// The search of [`bnb_changeless`], passing its first match to `finalize`.
+fn first_changeless_match<R: Rng, T>(
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+    match_parameters: &MatchParameters,
+    forbidden_pairs: &[(InputIndex, InputIndex)],
+    bnb_tries: &mut u32,
+    rng: &mut R,
+    finalize: impl FnOnce(&[usize]) -> Result<T, SelectionError>,
+) -> Result<T, SelectionError> {
+    let sorted_inputs = bnb_candidates(inputs, options);
+    let anchor_effective_value = anchor_effective_value(options);
+
+    // If every economical input is needed to exactly hit the target, there is nothing to search.
+    let mut economical_inputs: Vec<usize> = sorted_inputs.iter().map(|(index, _)| *index).collect();
+    economical_inputs.sort();
+    let total_effective_value = economical_inputs
+        .iter()
+        .filter_map(|&index| group_effective_value(&inputs[index], options).ok())
+        .fold(anchor_effective_value, u64::saturating_add);
+    // Spending every candidate falls short, so no branch can match. The candidates may be
+    // truncated to `max_depth`, and a selection with change may still pay for the target.
+    if total_effective_value < match_parameters.target_for_match {
+        let spendable_effective_value = inputs
+            .iter()
+            .filter(|input| !is_dust(input, options))
+            .filter_map(|input| group_effective_value(input, options).ok())
+            .fold(anchor_effective_value, u64::saturating_add);
+        if spendable_effective_value < options.target_value.saturating_add(base_fee(options)) {
+            return Err(SelectionError::InsufficientFunds);
+        }
+        return Err(SelectionError::NoSolutionFound);
+    }
+    let spends_forbidden_pair = economical_inputs
+        .iter()
+        .any(|&index| is_forbidden(index, &economical_inputs, forbidden_pairs));
+
+    // Spending a segwit input pays for the marker, and a saturated total is above the match
+    // window, see `MatchParameters::from_options`
+    let spent_effective_value = if total_effective_value < u64::MAX
+        && sorted_inputs.iter().any(|(_, input)| input.is_segwit)
+    {
+        total_effective_value.saturating_sub(segwit_marker_fee(options.target_feerate))
+    } else {
+        total_effective_value
+    };
+    if spent_effective_value == match_parameters.target_for_match && !spends_forbidden_pair {
+        return finalize(&economical_inputs);
+    }
+    bnb(
+        &sorted_inputs,
+        &mut Vec::new(),
+        anchor_effective_value,
+        0,
+        bnb_tries,
+        match_parameters,
+        forbidden_pairs,
+        rng,
+        finalize,
+    )?
+}
+
+/// Build the selection output of a Branch and Bound match.
+fn finalize_match(
+    inputs: &[OutputGroup],
+    selected_coin: Vec<usize>,
+    options: &CoinSelectionOpt,
+) -> Result<SelectionOutput, SelectionError> {
+    let (accumulated_value, accumulated_weight) =
+        selection_totals(inputs, &selected_coin, options)?;
+    finalize_selection(
+        inputs,
+        selected_coin,
+        options,
+        accumulated_value,
+        accumulated_weight,
+        Algorithm::Bnb,
+    )
+}
+
+/// Whether the Branch and Bound search of [`select_coin_bnb`] finds a changeless match, for the
+/// same `rng` state and search budget.
+/// The search only tracks the accumulated effective value and the depth of each branch over the
+/// sorted effective values of the candidates: no selection is built, so it is cheap on huge sets.
+/// Only the match window is checked, so a match that [`select_coin_bnb`] goes on to reject, e.g.
+/// for [`CoinSelectionOpt::max_excess_to_fee`] or [`CoinSelectionOpt::max_inputs`], still counts.
+/// Return false, if the options are invalid or the search budget runs out first.
+pub fn changeless_solution_exists(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+    rng: &mut impl Rng,
+) -> bool {
+    let Ok(match_parameters) = options
+        .validate()
+        .and_then(|()| MatchParameters::from_options(&options))
+    else {
+        return false;
+    };
+    // The candidates of `bnb_candidates`, as their effective value and whether they are segwit
+    let mut effective_values: Vec<(u64, u64, bool)> = inputs
+        .iter()
+        .filter(|input| !is_dust(input, &options) && meets_min_efficiency(input, &options))
+        .map(|input| {
+            let effective_value = group_effective_value(input, &options).unwrap_or(0);
+            (input.value, effective_value, input.is_segwit)
+        })
+        .collect();
+    effective_values.sort_by_key(|&(value, _, _)| core::cmp::Reverse(value));
+    if let Some(max_depth) = options.max_depth {
+        effective_values.truncate(max_depth);
+    }
+
+    let marker_fee = segwit_marker_fee(options.target_feerate);
+    let target = match_parameters.target_for_match;
+    let upper_bound = target + match_parameters.match_range;
+    // The effective value of the candidates from each depth on, leaving the marker out
+    let mut remaining_totals = vec![0u64; effective_values.len() + 1];
+    for depth in (0..effective_values.len()).rev() {
+        remaining_totals[depth] =
+            remaining_totals[depth + 1].saturating_add(effective_values[depth].1);
+    }
+    let anchor_effective_value = anchor_effective_value(&options);
+    // As in `first_changeless_match`: spending every candidate falls short, or exactly matches
+    let total_effective_value = anchor_effective_value.saturating_add(remaining_totals[0]);
+    if total_effective_value < target {
+        return false;
+    }
+    let pays_marker = effective_values.iter().any(|&(_, _, is_segwit)| is_segwit);
+    if total_effective_value < u64::MAX
+        && total_effective_value.saturating_sub(if pays_marker { marker_fee } else { 0 }) == target
+    {
+        return true;
+    }
+
+    // The branches left to explore, deepest last, as their accumulated effective value, depth
+    // and whether a segwit input is selected
+    let mut bnb_tries = bnb_budget(&options);
+    let mut branches = vec![(anchor_effective_value, 0, false)];
+    while let Some((acc_eff_value, depth, marker_paid)) = branches.pop() {
+        if (target..=upper_bound).contains(&acc_eff_value) {
+            return true;
+        }
+        if acc_eff_value > upper_bound
+            || acc_eff_value.saturating_add(remaining_totals[depth]) < target
+            || depth == effective_values.len()
+        {
+            continue;
+        }
+        // Out of tries, only the branches already pending are checked, as in `bnb_search`
+        if bnb_tries == 0 {
+            continue;
+        }
+        bnb_tries -= 1;
+        let (_, effective_value, is_segwit) = effective_values[depth];
+        let included = (
+            include_effective_value(
+                acc_eff_value,
+                effective_value,
+                is_segwit && !marker_paid,
+                marker_fee,
+            ),
+            depth + 1,
+            marker_paid || is_segwit,
+        );
+        let omitted = (acc_eff_value, depth + 1, marker_paid);
+        if rng.gen_bool(0.5) {
+            branches.extend([omitted, included]);
+        } else {
+            branches.extend([included, omitted]);
+        }
+    }
+    false
+}
+
+/// The inputs the Branch and Bound search considers, in descending value order.
+/// Uneconomical inputs, whose fee is at least their value, are dropped before sorting; they
+/// can never help reach the target and would only widen the search.
+pub(crate) fn bnb_candidates(
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+) -> Vec<(usize, OutputGroup)> {
+    let mut sorted_inputs: Vec<(usize, OutputGroup)> = inputs
+        .iter()
+        .enumerate()
+        .filter(|(_, input)| !is_dust(input, options))
+        .filter(|(_, input)| meets_min_efficiency(input, options))
+        .map(|(index, input)| (index, *input))
+        .collect();
+    sorted_inputs.sort_by_key(|(_, input)| core::cmp::Reverse(input.value));
+    if let Some(max_depth) = options.max_depth {
+        sorted_inputs.truncate(max_depth);
+    }
+    sorted_inputs
+}
+
+/// A decision node on the Branch and Bound backtracking stack.
+struct BnbFrame {
+    /// Accumulated effective value before deciding on this input.
+    acc_eff_value: u64,
+    /// Position of the input in the candidates.
+    depth: usize,
+    /// Whether the branch being explored includes the input.
+    included: bool,
+    /// Whether the other branch is still to be explored.
+    other_pending: bool,
+}
+
+/// Randomized Branch and Bound search, returning what `on_match` makes of the first match.
+/// Return NoSolutionFound, if the whole tree holds no match, and SearchBudgetExhausted, if the
+/// search gave up before finding one.
+/// `bnb_tries` is the search budget shared by every branch: each explored node spends exactly
+/// one try, whichever branch is taken first, and the search gives up once it is spent.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn bnb<R: Rng, T>(
+    inputs_in_desc_value: &[(usize, OutputGroup)],
+    selected_inputs: &mut Vec<usize>,
+    acc_eff_value: u64,
+    depth: usize,
+    bnb_tries: &mut u32,
+    match_parameters: &MatchParameters,
+    forbidden_pairs: &[(InputIndex, InputIndex)],
+    rng: &mut R,
+    on_match: impl FnOnce(&[usize]) -> T,
+) -> Result<T, SelectionError> {
+    let mut on_match = Some(on_match);
+    let mut found = None;
+    let exhausted = bnb_search(
+        inputs_in_desc_value,
+        selected_inputs,
+        acc_eff_value,
+        depth,
+        bnb_tries,
+        match_parameters,
+        forbidden_pairs,
+        true,
+        || rng.gen_bool(0.5),
+        |selected_inputs| {
+            found = on_match.take().map(|on_match| on_match(selected_inputs));
+            ControlFlow::Break(())
+        },
+    );
+    match found {
+        Some(found) => Ok(found),
+        None if exhausted => Err(SelectionError::SearchBudgetExhausted),
+        None => Err(SelectionError::NoSolutionFound),
+    }
+}
+
+/// The Branch and Bound traversal behind [`bnb`]: `include_first` decides whether the inclusion
+/// branch of an input is explored before the omission branch, and `on_match` is called with
+/// every match found, stopping the search once it breaks.
+/// With `prune_unreachable`, a branch whose remaining inputs cannot lift it to the target is cut
+/// without spending a try, so fruitless omission branches cost nothing.
+/// Returns whether the search ran out of `bnb_tries` with branches left to explore.
+/// The search backtracks over an explicit stack, so it runs in constant call stack space
+/// however many inputs there are.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn bnb_search(
+    inputs_in_desc_value: &[(usize, OutputGroup)],
+    selected_inputs: &mut Vec<usize>,
+    acc_eff_value: u64,
+    depth: usize,
+    bnb_tries: &mut u32,
+    match_parameters: &MatchParameters,
+    forbidden_pairs: &[(InputIndex, InputIndex)],
+    prune_unreachable: bool,
+    mut include_first: impl FnMut() -> bool,
+    mut on_match: impl FnMut(&[usize]) -> ControlFlow<()>,
+) -> bool {
+    let marker_fee = segwit_marker_fee(match_parameters.target_feerate);
+    // The effective values leave the segwit marker out, so the first segwit input of a selection
+    // pays for it, unless `marker_paid`.
+    let include = |acc_eff_value: u64, depth: usize, marker_paid: bool| {
+        // Every candidate is economical, see `bnb_candidates`.
+        let input = &inputs_in_desc_value[depth].1;
+        let effective_value = group_effective_value_at(
+            input,
+            match_parameters.target_feerate,
+            match_parameters.cost_per_input,
+        )
+        .unwrap_or(0);
+        include_effective_value(
+            acc_eff_value,
+            effective_value,
+            input.is_segwit && !marker_paid,
+            marker_fee,
+        )
+    };
+    // The effective value of the inputs from each depth on, saturated like the accumulated one.
+    // Leaving the marker out keeps it an upper bound.
+    let mut remaining_totals = vec![0u64; inputs_in_desc_value.len() + 1];
+    for depth in (0..inputs_in_desc_value.len()).rev() {
+        remaining_totals[depth] = include(remaining_totals[depth + 1], depth, true);
+    }
+    let reachable = |acc_eff_value: u64, depth: usize| {
+        !prune_unreachable
+            || acc_eff_value.saturating_add(remaining_totals[depth])
+                >= match_parameters.target_for_match
+    };
+    let input_count = |depth: usize| inputs_in_desc_value[depth].1.input_count;
+    let segwit_count = |depth: usize| usize::from(inputs_in_desc_value[depth].1.is_segwit);
+    let mut stack: Vec<BnbFrame> = Vec::new();
+    let mut next = (acc_eff_value, depth);
+    let mut exhausted = false;
+    // The inputs spent by the selected groups, and how many of the groups are segwit, counting
+    // from an empty selection.
+    let mut selected_input_count = 0;
+    let mut selected_segwit_count = 0;
+    loop {
+        let (acc_eff_value, depth) = next;
+        if acc_eff_value >= match_parameters.target_for_match
+            && acc_eff_value <= match_parameters.target_for_match + match_parameters.match_range
+        {
+            if on_match(selected_inputs).is_break() {
+                return exhausted;
+            }
+        } else if acc_eff_value < match_parameters.target_for_match
+            && !reachable(acc_eff_value, depth)
+        {
+            // Even every remaining input falls short, the branch holds no match
+        } else if acc_eff_value < match_parameters.target_for_match
+            && *bnb_tries > 0
+            && depth < inputs_in_desc_value.len()
+        {
+            *bnb_tries -= 1;
+            // The inclusion branch is pruned if this input is forbidden alongside a selected one,
+            // or would take the selection past `max_inputs`
+            let forbidden = is_forbidden(
+                inputs_in_desc_value[depth].0,
+                selected_inputs,
+                forbidden_pairs,
+            ) || match_parameters
+                .max_inputs
+                .is_some_and(|max_inputs| selected_input_count + input_count(depth) > max_inputs);
+            let included = !forbidden && include_first();
+            if included {
+                selected_inputs.push(inputs_in_desc_value[depth].0);
+                selected_input_count += input_count(depth);
+                next = (
+                    include(acc_eff_value, depth, selected_segwit_count > 0),
+                    depth + 1,
+                );
+                selected_segwit_count += segwit_count(depth);
+            } else {
+                next = (acc_eff_value, depth + 1);
+            }
+            stack.push(BnbFrame {
+                acc_eff_value,
+                depth,
+                included,
+                other_pending: !forbidden,
+            });
+            continue;
+        } else if acc_eff_value < match_parameters.target_for_match
+            && depth < inputs_in_desc_value.len()
+        {
+            // Out of tries, the branch is left unexplored
+            exhausted = true;
+        }
+
+        // The branch is done: backtrack to the deepest node with a branch left to explore
+        loop {
+            let Some(frame) = stack.last_mut() else {
+                return exhausted;
+            };
+            if frame.included {
+                selected_inputs.pop();
+                selected_input_count -= input_count(frame.depth);
+                selected_segwit_count -= segwit_count(frame.depth);
+            }
+            if frame.other_pending {
+                frame.other_pending = false;
+                frame.included = !frame.included;
+                if frame.included {
+                    selected_inputs.push(inputs_in_desc_value[frame.depth].0);
+                    selected_input_count += input_count(frame.depth);
+                    next = (
+                        include(frame.acc_eff_value, frame.depth, selected_segwit_count > 0),
+                        frame.depth + 1,
+                    );
+                    selected_segwit_count += segwit_count(frame.depth);
+                } else {
+                    next = (frame.acc_eff_value, frame.depth + 1);
+                }
+                break;
+            }
+            stack.pop();
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use alloc::vec;
+
+use rand::Rng;
+
+use super::{fifo::select_coin_fifo, lowestlarger::select_coin_lowestlarger, srd::select_coin_srd};
+use crate::{
+    types::{CoinSelectionOpt, OutputGroup, SelectionError, SelectionOutput},
+    utils::compare_selections,
+};
+
+/// Perform Coinselection preferring the solution whose summed coin age is closest to
+/// [`CoinSelectionOpt::target_coin_age`], with ties going to the lower waste.
+/// The age of an input is how many sequence numbers it is older than the newest input; inputs
+/// without a `creation_sequence` have age 0.
+/// Candidates come from FIFO, Lowest Larger and repeated Single Random Draws.
+/// Without a `target_coin_age`, or between candidates as close to it, the best candidate by
+/// [`compare_selections`](crate::compare_selections) is returned.
+#[must_use = "the selection result tells which inputs to spend"]
+pub fn select_coin_coin_age(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+    rng: &mut impl Rng,
+) -> Result<SelectionOutput, SelectionError> {
+    options.validate()?;
+    const COIN_AGE_DRAWS: usize = 100;
+
+    let newest = inputs
+        .iter()
+        .filter_map(|input| input.creation_sequence)
+        .max()
+        .unwrap_or(0);
+    let coin_age = |selection: &SelectionOutput| -> u64 {
+        selection
+            .selected_inputs
+            .iter()
+            .filter_map(|&index| inputs[index].creation_sequence)
+            .map(|sequence| u64::from(newest - sequence))
+            .sum()
+    };
+
+    let mut candidates = vec![
+        select_coin_fifo(inputs, options),
+        select_coin_lowestlarger(inputs, options),
+    ];
+    candidates.extend((0..COIN_AGE_DRAWS).map(|_| select_coin_srd(inputs, options, rng)));
+
+    let mut error = SelectionError::InsufficientFunds;
+    let mut best: Option<SelectionOutput> = None;
+    for candidate in candidates {
+        let candidate = match candidate {
+            Ok(candidate) => candidate,
+            Err(err) => {
+                error = err;
+                continue;
+            }
+        };
+        let age_distance = |selection: &SelectionOutput| {
+            options
+                .target_coin_age
+                .map_or(0, |target| coin_age(selection).abs_diff(target))
+        };
+        if best.as_ref().is_none_or(|best| {
+            age_distance(&candidate)
+                .cmp(&age_distance(best))
+                .then_with(|| compare_selections(inputs, &candidate, best, &options))
+                .is_lt()
+        }) {
+            best = Some(candidate);
+        }
+    }
+    best.ok_or(error)
+}
@@ -0,0 +1,33 @@
+use alloc::vec::Vec;
+
+use super::select_in_order;
+use crate::{
+    types::{Algorithm, CoinSelectionOpt, OutputGroup, SelectionError, SelectionOutput},
+    utils::{is_dust, meets_min_efficiency, spendable_with_change},
+};
+
+/// Perform Coinselection via First-In-First-Out algorithm.
+/// Inputs are spent in ascending `creation_sequence`, those without one last as the newest, until
+/// their effective value covers the target and the change requirements.
+/// Return InsufficientFunds, if all inputs together fall short.
+#[must_use = "the selection result tells which inputs to spend"]
+pub fn select_coin_fifo(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+) -> Result<SelectionOutput, SelectionError> {
+    options.validate()?;
+
+    // Sorting the inputs vector based on creation_sequence
+
+    let mut sorted_inputs: Vec<_> = inputs
+        .iter()
+        .enumerate()
+        .filter(|(_, input)| spendable_with_change(input, &options))
+        .filter(|(_, input)| meets_min_efficiency(input, &options))
+        .filter(|(_, input)| !is_dust(input, &options))
+        .collect();
+
+    sorted_inputs.sort_by_key(|(_, a)| (a.creation_sequence.is_none(), a.creation_sequence));
+
+    select_in_order(inputs, &options, sorted_inputs, Algorithm::Fifo)
+}
@@ -0,0 +1,177 @@
+use alloc::{vec, vec::Vec};
+
+use rand::Rng;
+
+use super::optimize_subset;
+use crate::{
+    types::{Algorithm, CoinSelectionOpt, OutputGroup, SelectionError, SelectionOutput},
+    utils::{
+        anchor_effective_value, compare_selections, covers_target, finalize_selection,
+        group_effective_value, is_dust, meets_min_efficiency, required_drain_value,
+        selection_totals, spendable_with_change, transaction_fee,
+    },
+};
+
+/// The number of random draws [`knap_sack`] makes over the smaller coins.
+const KNAPSACK_ITERATIONS: usize = 1000;
+
+/// The number of rounds [`optimize_subset`] refines the drawn subset for.
+const KNAPSACK_OPTIMIZE_PASSES: usize = 100;
+
+/// Perform Coinselection via Knapsack solver, as Bitcoin Core does.
+/// A coin whose effective value matches the target exactly is spent alone. Otherwise the coins
+/// below the target are searched for the smallest subset that covers it by random draws. That
+/// subset is refined by random additions and removals that lower its waste, and the least waste
+/// of the drawn subset, its refinement and the lowest larger coin is returned.
+/// Return InsufficientFunds, if the total effective value is below the target.
+#[must_use = "the selection result tells which inputs to spend"]
+pub fn select_coin_knapsack(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+    rng: &mut impl Rng,
+) -> Result<SelectionOutput, SelectionError> {
+    options.validate()?;
+    // The effective values pay for the inputs, the rest of the transaction on top of the target
+    let adjusted_target = options
+        .target_value
+        .checked_add(required_drain_value(&options))
+        .and_then(|target| target.checked_add(transaction_fee(&options, 0).ok()?))
+        .ok_or(SelectionError::Overflow)?
+        .saturating_sub(anchor_effective_value(&options));
+
+    let mut smaller_coins: Vec<(usize, u64)> = Vec::new();
+    let mut lowest_larger: Option<(usize, u64)> = None;
+    for (index, input) in inputs.iter().enumerate() {
+        if !spendable_with_change(input, &options)
+            || !meets_min_efficiency(input, &options)
+            || is_dust(input, &options)
+        {
+            continue;
+        }
+        let Ok(value) = group_effective_value(input, &options) else {
+            continue;
+        };
+        if value < adjusted_target {
+            smaller_coins.push((index, value));
+        } else if lowest_larger.is_none_or(|(_, lowest)| value < lowest) {
+            lowest_larger = Some((index, value));
+        }
+    }
+    let smaller_total = smaller_coins
+        .iter()
+        .fold(0u64, |total, &(_, value)| total.saturating_add(value));
+    if lowest_larger.is_none() && smaller_total < adjusted_target {
+        return Err(SelectionError::InsufficientFunds);
+    }
+    smaller_coins.sort_by_key(|&(_, value)| core::cmp::Reverse(value));
+
+    let mut candidates: Vec<Vec<usize>> = Vec::new();
+    match lowest_larger {
+        Some((index, value)) if value == adjusted_target => candidates.push(vec![index]),
+        _ => {
+            if smaller_total >= adjusted_target {
+                let drawn = knap_sack(adjusted_target, &smaller_coins, rng);
+                let refined =
+                    optimize_subset(inputs, &drawn, &options, rng, KNAPSACK_OPTIMIZE_PASSES);
+                if refined != drawn {
+                    candidates.push(refined);
+                }
+                candidates.push(drawn);
+            }
+            if let Some((index, _)) = lowest_larger {
+                candidates.push(vec![index]);
+            }
+        }
+    }
+
+    let mut error = SelectionError::NoSolutionFound;
+    let mut best: Option<SelectionOutput> = None;
+    for selected_inputs in candidates {
+        let (accumulated_value, accumulated_weight) =
+            selection_totals(inputs, &selected_inputs, &options)?;
+        if !covers_target(&options, accumulated_value, accumulated_weight) {
+            continue;
+        }
+        let candidate = match finalize_selection(
+            inputs,
+            selected_inputs,
+            &options,
+            accumulated_value,
+            accumulated_weight,
+            Algorithm::Knapsack,
+        ) {
+            Ok(candidate) => candidate,
+            Err(err) => {
+                error = err;
+                continue;
+            }
+        };
+        if best
+            .as_ref()
+            .is_none_or(|best| compare_selections(inputs, &candidate, best, &options).is_lt())
+        {
+            best = Some(candidate);
+        }
+    }
+    best.ok_or(error)
+}
+
+/// Approximate the smallest subset of `smaller_coins` whose effective value reaches
+/// `adjusted_target`, by [`KNAPSACK_ITERATIONS`] random draws.
+/// `smaller_coins` pairs the index of each coin in the inputs with its effective value; it should
+/// be sorted in descending order of value, every value below `adjusted_target` and the total at
+/// least `adjusted_target`.
+fn knap_sack<R: Rng>(
+    adjusted_target: u64,
+    smaller_coins: &[(usize, u64)],
+    rng: &mut R,
+) -> Vec<usize> {
+    let mut best_included = vec![true; smaller_coins.len()];
+    let mut best_total = smaller_coins
+        .iter()
+        .fold(0u64, |total, &(_, value)| total.saturating_add(value));
+
+    for _ in 0..KNAPSACK_ITERATIONS {
+        if best_total == adjusted_target {
+            break;
+        }
+        let mut included = vec![false; smaller_coins.len()];
+        let mut total: u64 = 0;
+        let mut reached_target = false;
+        // A random half of the coins on the first pass, then the rest in order.
+        for pass in 0..2 {
+            if reached_target {
+                break;
+            }
+            for (position, &(_, value)) in smaller_coins.iter().enumerate() {
+                let draw = if pass == 0 {
+                    rng.gen_bool(0.5)
+                } else {
+                    !included[position]
+                };
+                if !draw {
+                    continue;
+                }
+                total = total.saturating_add(value);
+                included[position] = true;
+                if total >= adjusted_target {
+                    reached_target = true;
+                    if total < best_total {
+                        best_total = total;
+                        best_included.clone_from(&included);
+                    }
+                    // Try the next coin in place of this one.
+                    total -= value;
+                    included[position] = false;
+                }
+            }
+        }
+    }
+
+    smaller_coins
+        .iter()
+        .zip(best_included)
+        .filter(|(_, included)| *included)
+        .map(|(&(index, _), _)| index)
+        .collect()
+}
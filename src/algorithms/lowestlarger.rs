@@ -0,0 +1,125 @@
+use alloc::{vec, vec::Vec};
+
+use crate::{
+    types::{Algorithm, CoinSelectionOpt, OutputGroup, SelectionError, SelectionOutput},
+    utils::{
+        accumulate, calculate_fee, covers_target, effective_value, finalize_selection, is_dust,
+        meets_min_efficiency, required_drain_value, required_value, spendable_with_change,
+        transaction_fee,
+    },
+};
+
+/// Perform Coinselection via Lowest Larger algorithm.
+/// Return NoSolutionFound, if no solution exists.
+#[must_use = "the selection result tells which inputs to spend"]
+pub fn select_coin_lowestlarger(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+) -> Result<SelectionOutput, SelectionError> {
+    options.validate()?;
+    let (mut accumulated_value, mut accumulated_weight) = options.anchor.unwrap_or((0, 0));
+    let mut selected_inputs: Vec<usize> = Vec::new();
+    let mut has_segwit = false;
+    let target = options
+        .target_value
+        .checked_add(required_drain_value(&options))
+        .ok_or(SelectionError::Overflow)?;
+
+    let mut sorted_inputs: Vec<_> = inputs
+        .iter()
+        .enumerate()
+        .filter(|(_, input)| spendable_with_change(input, &options))
+        .filter(|(_, input)| meets_min_efficiency(input, &options))
+        .filter(|(_, input)| !is_dust(input, &options))
+        .collect();
+    sorted_inputs.sort_by_key(|(_, input)| effective_value(input, &options));
+
+    let index = sorted_inputs.partition_point(|(_, input)| {
+        input.value <= target.saturating_add(calculate_fee(input.weight, options.target_feerate))
+    });
+
+    for (idx, input) in sorted_inputs.iter().take(index).rev() {
+        (accumulated_value, accumulated_weight) = accumulate(
+            (accumulated_value, accumulated_weight),
+            input,
+            &mut has_segwit,
+        )?;
+        selected_inputs.push(*idx);
+
+        if accumulated_value
+            >= required_value(&options, transaction_fee(&options, accumulated_weight)?)?
+        {
+            break;
+        }
+    }
+
+    if accumulated_value < required_value(&options, transaction_fee(&options, accumulated_weight)?)?
+    {
+        for (idx, input) in sorted_inputs.iter().skip(index) {
+            (accumulated_value, accumulated_weight) = accumulate(
+                (accumulated_value, accumulated_weight),
+                input,
+                &mut has_segwit,
+            )?;
+            selected_inputs.push(*idx);
+
+            if accumulated_value
+                >= required_value(&options, transaction_fee(&options, accumulated_weight)?)?
+            {
+                break;
+            }
+        }
+    }
+
+    if accumulated_value < required_value(&options, transaction_fee(&options, accumulated_weight)?)?
+    {
+        Err(SelectionError::InsufficientFunds)
+    } else {
+        finalize_selection(
+            inputs,
+            selected_inputs,
+            &options,
+            accumulated_value,
+            accumulated_weight,
+            Algorithm::LowestLarger,
+        )
+    }
+}
+
+/// Perform Coinselection spending the single input of lowest value that alone covers the target,
+/// the fee and the required change. Unlike [`select_coin_lowestlarger`], it never tops up a
+/// smaller input with others.
+/// Return NoSolutionFound, if no single input suffices.
+#[must_use = "the selection result tells which inputs to spend"]
+pub fn select_coin_single_lowest_larger(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+) -> Result<SelectionOutput, SelectionError> {
+    options.validate()?;
+    let anchor = options.anchor.unwrap_or((0, 0));
+    let mut lowest_larger: Option<(usize, (u64, u32))> = None;
+    for (index, input) in inputs.iter().enumerate() {
+        if !spendable_with_change(input, &options)
+            || !meets_min_efficiency(input, &options)
+            || is_dust(input, &options)
+        {
+            continue;
+        }
+        let (accumulated_value, accumulated_weight) = accumulate(anchor, input, &mut false)?;
+        if covers_target(&options, accumulated_value, accumulated_weight)
+            && lowest_larger.is_none_or(|(lowest, _)| input.value < inputs[lowest].value)
+        {
+            lowest_larger = Some((index, (accumulated_value, accumulated_weight)));
+        }
+    }
+    let (index, (accumulated_value, accumulated_weight)) =
+        lowest_larger.ok_or(SelectionError::NoSolutionFound)?;
+    finalize_selection(
+        inputs,
+        vec![index],
+        &options,
+        accumulated_value,
+        accumulated_weight,
+        Algorithm::SingleLowestLarger,
+    )
+}
@@ -0,0 +1,75 @@
+use alloc::vec::Vec;
+
+use super::{fifo::select_coin_fifo, lowestlarger::select_coin_lowestlarger, select_in_order};
+use crate::{
+    types::{Algorithm, CoinSelectionOpt, OutputGroup, SelectionError, SelectionOutput},
+    utils::{compare_selections, is_dust, meets_min_efficiency, spendable_with_change},
+};
+
+/// Perform Coinselection preferring the solution with the lowest summed risk, where `scores`
+/// holds an external risk score (e.g. a taint probability) for each of the `inputs`.
+/// Candidates come from spending the least risky inputs first, FIFO and Lowest Larger; ties in
+/// risk fall back to [`compare_selections`](crate::compare_selections).
+/// Return MissingRiskScores, if `scores` is shorter than `inputs`.
+#[must_use = "the selection result tells which inputs to spend"]
+pub fn select_coin_min_risk(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+    scores: &[f32],
+) -> Result<SelectionOutput, SelectionError> {
+    options.validate()?;
+    if scores.len() < inputs.len() {
+        return Err(SelectionError::MissingRiskScores);
+    }
+    let risk = |selection: &SelectionOutput| -> f32 {
+        selection
+            .selected_inputs
+            .iter()
+            .map(|&index| scores[index.0])
+            .sum()
+    };
+
+    let candidates = [
+        least_risky_first(inputs, options, scores),
+        select_coin_fifo(inputs, options),
+        select_coin_lowestlarger(inputs, options),
+    ];
+    let mut error = SelectionError::InsufficientFunds;
+    let mut best: Option<SelectionOutput> = None;
+    for candidate in candidates {
+        let candidate = match candidate {
+            Ok(candidate) => candidate,
+            Err(err) => {
+                error = err;
+                continue;
+            }
+        };
+        if best.as_ref().is_none_or(|best| {
+            risk(&candidate)
+                .total_cmp(&risk(best))
+                .then_with(|| compare_selections(inputs, &candidate, best, &options))
+                .is_lt()
+        }) {
+            best = Some(candidate);
+        }
+    }
+    best.ok_or(error)
+}
+
+/// Accumulate the inputs in ascending order of their risk `scores` until the target is covered.
+fn least_risky_first(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+    scores: &[f32],
+) -> Result<SelectionOutput, SelectionError> {
+    let mut sorted_inputs: Vec<_> = inputs
+        .iter()
+        .enumerate()
+        .filter(|(_, input)| spendable_with_change(input, &options))
+        .filter(|(_, input)| meets_min_efficiency(input, &options))
+        .filter(|(_, input)| !is_dust(input, &options))
+        .collect();
+    sorted_inputs.sort_by(|(a, _), (b, _)| scores[*a].total_cmp(&scores[*b]));
+
+    select_in_order(inputs, &options, sorted_inputs, Algorithm::MinRisk)
+}
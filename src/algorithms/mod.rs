@@ -0,0 +1,290 @@
+//! The selection algorithms, and the wrappers that combine them.
+
+pub(crate) mod anchor_topup;
+pub(crate) mod bnb;
+pub(crate) mod coin_age;
+pub(crate) mod fifo;
+pub(crate) mod knapsack;
+pub(crate) mod largest_first;
+pub(crate) mod lowestlarger;
+pub(crate) mod min_risk;
+pub(crate) mod optimal;
+pub(crate) mod recent_change;
+pub(crate) mod smallest_first;
+pub(crate) mod srd;
+
+use alloc::vec::Vec;
+
+use rand::{seq::SliceRandom, Rng};
+
+use self::bnb::MatchParameters;
+use crate::{
+    types::{
+        Algorithm, CoinSelectionOpt, InputIndex, OutputGroup, Pool, SelectionError,
+        SelectionOutput, SelectionReason,
+    },
+    utils::{
+        accumulate, anchor_effective_value, compare_selections, compute_waste, covers_target,
+        finalize_selection, group_effective_value, is_dust, meets_min_efficiency, required_value,
+        selection_totals, spendable_with_change, transaction_fee,
+    },
+};
+
+/// Perform Coinselection with the `forced` inputs always spent.
+/// The forced inputs are folded into [`CoinSelectionOpt::anchor`] and `select` tops up from the
+/// remaining inputs. Indices in the result, including the audit, refer to `inputs`.
+#[must_use = "the selection result tells which inputs to spend"]
+pub fn select_coin_with_forced<F>(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+    forced: &[InputIndex],
+    select: F,
+) -> Result<SelectionOutput, SelectionError>
+where
+    F: FnOnce(&[OutputGroup], CoinSelectionOpt) -> Result<SelectionOutput, SelectionError>,
+{
+    let mut forced_options = options;
+    forced_options.anchor = Some(selection_totals(inputs, forced, &options)?);
+
+    let (remaining_indices, remaining_inputs): (Vec<InputIndex>, Vec<OutputGroup>) = inputs
+        .iter()
+        .enumerate()
+        .map(|(index, input)| (InputIndex(index), *input))
+        .filter(|(index, _)| !forced.contains(index))
+        .unzip();
+    let mut selection = select(&remaining_inputs, forced_options)?;
+    selection.remap(&remaining_indices);
+
+    let topped_up = core::mem::take(&mut selection.selected_inputs);
+    selection.selected_inputs = forced.iter().copied().chain(topped_up).collect();
+    if let Some(audit) = selection.audit.take() {
+        let forced_audit = forced.iter().map(|&index| (index, SelectionReason::Forced));
+        selection.audit = Some(forced_audit.chain(audit).collect());
+    }
+    Ok(selection)
+}
+
+/// Perform Coinselection for a replace-by-fee bump of a transaction spending the
+/// `previous_selection`, e.g. at a higher `target_feerate`. The replacement spends every previous
+/// input, so it conflicts with the original, and tops up with the fewest extra inputs, largest
+/// first. `min_absolute_fee` is the fee the original paid; the replacement pays at least one sat
+/// more. Indices in the result refer to `inputs`.
+/// Return InsufficientFunds, if all inputs together do not cover the bumped fee.
+#[must_use = "the selection result tells which inputs to spend"]
+pub fn select_coin_rbf(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+    previous_selection: &[InputIndex],
+) -> Result<SelectionOutput, SelectionError> {
+    options.validate()?;
+    let bumped_options = CoinSelectionOpt {
+        min_absolute_fee: options
+            .min_absolute_fee
+            .checked_add(1)
+            .ok_or(SelectionError::Overflow)?,
+        ..options
+    };
+    select_coin_with_forced(
+        inputs,
+        bumped_options,
+        previous_selection,
+        largest_first::select_coin_largest_first,
+    )
+}
+
+/// Perform Coinselection with `select`, guaranteeing at least one confirmed input, i.e. one with a
+/// `creation_sequence`, so the transaction is not built from unconfirmed inputs alone.
+/// If the plain selection has none, each confirmed input is tried as a forced input and the
+/// least waste result is returned.
+/// Return NoConfirmedInput, if no selection covering the target spends a confirmed input.
+#[must_use = "the selection result tells which inputs to spend"]
+pub fn select_coin_with_confirmed_input<F>(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+    select: F,
+) -> Result<SelectionOutput, SelectionError>
+where
+    F: Fn(&[OutputGroup], CoinSelectionOpt) -> Result<SelectionOutput, SelectionError>,
+{
+    let is_confirmed = |index: &InputIndex| inputs[*index].creation_sequence.is_some();
+    let selection = select(inputs, options)?;
+    if selection.selected_inputs.iter().any(is_confirmed) {
+        return Ok(selection);
+    }
+    (0..inputs.len())
+        .map(InputIndex)
+        .filter(is_confirmed)
+        .filter_map(|index| select_coin_with_forced(inputs, options, &[index], &select).ok())
+        .min()
+        .ok_or(SelectionError::NoConfirmedInput)
+}
+
+/// Perform Coinselection with `select` on the [`Pool::Spending`] inputs only, falling back to
+/// all inputs when they are insufficient. Indices in the result refer to `inputs`.
+#[must_use = "the selection result tells which inputs to spend"]
+pub fn select_coin_from_pools<F>(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+    select: F,
+) -> Result<SelectionOutput, SelectionError>
+where
+    F: Fn(&[OutputGroup], CoinSelectionOpt) -> Result<SelectionOutput, SelectionError>,
+{
+    let (spending_indices, spending_inputs): (Vec<InputIndex>, Vec<OutputGroup>) = inputs
+        .iter()
+        .enumerate()
+        .filter(|(_, input)| input.pool == Pool::Spending)
+        .map(|(index, input)| (InputIndex(index), *input))
+        .unzip();
+    match select(&spending_inputs, options) {
+        Ok(mut selection) => {
+            selection.remap(&spending_indices);
+            Ok(selection)
+        }
+        Err(SelectionError::InsufficientFunds) => select(inputs, options),
+        Err(err) => Err(err),
+    }
+}
+
+/// The Global Coinselection API that runs Branch and Bound, Knapsack and Single Random Draw and
+/// returns the best result by [`compare_selections`](crate::compare_selections), i.e. the one with
+/// least [WasteMetric](crate::WasteMetric).
+/// If every algorithm fails, return InsufficientFunds if the inputs' total value is below the
+/// target, Overflow if it does not fit in a `u64`, InsufficientFundsForFee if their total
+/// effective value is below the Branch and Bound target, and NoSolutionFound otherwise.
+#[must_use = "the selection result tells which inputs to spend"]
+pub fn select_coin(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+    rng: &mut impl Rng,
+) -> Result<SelectionOutput, SelectionError> {
+    options.validate()?;
+    let candidates = [
+        bnb::select_coin_bnb_changeless(inputs, options, rng),
+        knapsack::select_coin_knapsack(inputs, options, rng),
+        srd::select_coin_srd(inputs, options, rng),
+    ];
+    let mut best: Option<SelectionOutput> = None;
+    for candidate in candidates.into_iter().flatten() {
+        if best
+            .as_ref()
+            .is_none_or(|best| compare_selections(inputs, &candidate, best, &options).is_lt())
+        {
+            best = Some(candidate);
+        }
+    }
+    best.ok_or_else(|| {
+        let total_value = inputs
+            .iter()
+            .try_fold(0u64, |total, input| total.checked_add(input.value));
+        match total_value {
+            None => SelectionError::Overflow,
+            Some(total_value) if total_value < options.target_value => {
+                SelectionError::InsufficientFunds
+            }
+            Some(_) if !affords_fee(inputs, &options) => SelectionError::InsufficientFundsForFee,
+            Some(_) => SelectionError::NoSolutionFound,
+        }
+    })
+}
+
+/// Whether the inputs' total effective value reaches the Branch and Bound target, i.e. spending
+/// every economical input pays for the target and the fee.
+fn affords_fee(inputs: &[OutputGroup], options: &CoinSelectionOpt) -> bool {
+    let Ok(match_parameters) = MatchParameters::from_options(options) else {
+        return true;
+    };
+    let total_effective_value = inputs
+        .iter()
+        .filter_map(|input| group_effective_value(input, options).ok())
+        .fold(anchor_effective_value(options), u64::saturating_add);
+    total_effective_value >= match_parameters.target_for_match()
+}
+
+/// Spend the `sorted_inputs` in order until they cover the target, the fee and the required
+/// change: the loop of the selectors that only differ in how they order the inputs.
+/// Return InsufficientFunds, if all of them together fall short.
+pub(crate) fn select_in_order<'a>(
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+    sorted_inputs: impl IntoIterator<Item = (usize, &'a OutputGroup)>,
+    chosen_algorithm: Algorithm,
+) -> Result<SelectionOutput, SelectionError> {
+    let (mut accumulated_value, mut accumulated_weight) = options.anchor.unwrap_or((0, 0));
+    let mut selected_inputs: Vec<usize> = Vec::new();
+    let mut has_segwit = false;
+    for (index, input) in sorted_inputs {
+        if covers_target(options, accumulated_value, accumulated_weight) {
+            break;
+        }
+        (accumulated_value, accumulated_weight) = accumulate(
+            (accumulated_value, accumulated_weight),
+            input,
+            &mut has_segwit,
+        )?;
+        selected_inputs.push(index);
+    }
+    if accumulated_value < required_value(options, transaction_fee(options, accumulated_weight)?)? {
+        return Err(SelectionError::InsufficientFunds);
+    }
+    finalize_selection(
+        inputs,
+        selected_inputs,
+        options,
+        accumulated_value,
+        accumulated_weight,
+        chosen_algorithm,
+    )
+}
+
+/// Improve a selection by random single-input additions and removals over `passes` rounds,
+/// returning the lowest-waste variant found, by [`compute_waste`](crate::compute_waste).
+/// `selected` should cover the target; only variants that keep covering it are accepted, and only
+/// inputs the selectors may spend with change are added.
+pub(crate) fn optimize_subset(
+    inputs: &[OutputGroup],
+    selected: &[usize],
+    options: &CoinSelectionOpt,
+    rng: &mut impl Rng,
+    passes: usize,
+) -> Vec<usize> {
+    let spendable: Vec<usize> = (0..inputs.len())
+        .filter(|&index| {
+            let input = &inputs[index];
+            spendable_with_change(input, options)
+                && meets_min_efficiency(input, options)
+                && !is_dust(input, options)
+        })
+        .collect();
+    let mut best = selected.to_vec();
+    let mut best_waste = compute_waste(inputs, &best, options);
+    let mut current = best.clone();
+
+    for _ in 0..passes {
+        let mut candidate = current.clone();
+        let unselected: Vec<usize> = spendable
+            .iter()
+            .copied()
+            .filter(|index| !candidate.contains(index))
+            .collect();
+        if !candidate.is_empty() && (unselected.is_empty() || rng.gen_bool(0.5)) {
+            candidate.swap_remove(rng.gen_range(0..candidate.len()));
+        } else if let Some(&index) = unselected.choose(rng) {
+            candidate.push(index);
+        }
+
+        let Ok((value, weight)) = selection_totals(inputs, &candidate, options) else {
+            continue;
+        };
+        if !covers_target(options, value, weight) {
+            continue;
+        }
+        let waste = compute_waste(inputs, &candidate, options);
+        if waste < best_waste {
+            best.clone_from(&candidate);
+            best_waste = waste;
+        }
+        current = candidate;
+    }
+    best
+}
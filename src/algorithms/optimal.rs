@@ -0,0 +1,85 @@
+use alloc::vec::Vec;
+
+use crate::{
+    algorithms::bnb::{in_match_window, MatchParameters},
+    types::{Algorithm, CoinSelectionOpt, OutputGroup, SelectionError, SelectionOutput},
+    utils::{
+        compare_selections, covers_target, finalize_changeless, finalize_selection, is_dust,
+        meets_min_efficiency, selection_totals, spendable_with_change,
+    },
+};
+
+/// The largest input set [`select_coin_optimal`] enumerates.
+const OPTIMAL_MAX_INPUTS: usize = 24;
+
+/// Perform Coinselection by enumerating every subset of the inputs and returning the least waste
+/// one that covers the target, to validate the heuristics against on small sets.
+/// A subset within the Branch and Bound match window is also scored as changeless, its excess
+/// paid as fee, so the optimum is never worse than a Branch and Bound match.
+/// The subsets are ranked by [`compare_selections`](crate::compare_selections).
+/// Return TooManyInputs, if there are more than 24 inputs.
+#[doc(alias = "select_coin_exhaustive")]
+#[must_use = "the selection result tells which inputs to spend"]
+pub fn select_coin_optimal(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+) -> Result<SelectionOutput, SelectionError> {
+    options.validate()?;
+    if inputs.len() > OPTIMAL_MAX_INPUTS {
+        return Err(SelectionError::TooManyInputs);
+    }
+    let match_parameters = MatchParameters::from_options(&options).ok();
+    let candidates: Vec<usize> = inputs
+        .iter()
+        .enumerate()
+        .filter(|(_, input)| meets_min_efficiency(input, &options))
+        .filter(|(_, input)| !is_dust(input, &options))
+        .map(|(index, _)| index)
+        .collect();
+
+    let mut error = SelectionError::InsufficientFunds;
+    let mut best: Option<SelectionOutput> = None;
+    for subset_mask in 1u32..1 << candidates.len() {
+        let subset: Vec<usize> = candidates
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| subset_mask & (1 << bit) != 0)
+            .map(|(_, &index)| index)
+            .collect();
+        let Ok((value, weight)) = selection_totals(inputs, &subset, &options) else {
+            continue;
+        };
+        let changeless = match_parameters.is_some_and(|match_parameters| {
+            in_match_window(inputs, &subset, &options, &match_parameters)
+        });
+        let with_change = covers_target(&options, value, weight)
+            && subset
+                .iter()
+                .all(|&index| spendable_with_change(&inputs[index], &options));
+        let selections = [
+            changeless.then(|| {
+                let subset = subset.clone();
+                finalize_changeless(inputs, subset, &options, value, weight, Algorithm::Optimal)
+            }),
+            with_change.then(|| {
+                finalize_selection(inputs, subset, &options, value, weight, Algorithm::Optimal)
+            }),
+        ];
+        for selection in selections.into_iter().flatten() {
+            let selection = match selection {
+                Ok(selection) => selection,
+                Err(err) => {
+                    error = err;
+                    continue;
+                }
+            };
+            if best
+                .as_ref()
+                .is_none_or(|best| compare_selections(inputs, &selection, best, &options).is_lt())
+            {
+                best = Some(selection);
+            }
+        }
+    }
+    best.ok_or(error)
+}
@@ -0,0 +1,29 @@
+use alloc::vec::Vec;
+
+use super::select_in_order;
+use crate::{
+    types::{Algorithm, CoinSelectionOpt, OutputGroup, SelectionError, SelectionOutput},
+    utils::{is_dust, meets_min_efficiency, spendable_with_change},
+};
+
+/// Perform Coinselection spending the newest inputs first, e.g. to move recent change on quickly.
+/// Inputs without a `creation_sequence` are treated as the oldest.
+/// Return InsufficientFunds, if all inputs do not cover the target.
+#[must_use = "the selection result tells which inputs to spend"]
+pub fn select_coin_recent_change(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+) -> Result<SelectionOutput, SelectionError> {
+    options.validate()?;
+    let mut sorted_inputs: Vec<_> = inputs
+        .iter()
+        .enumerate()
+        .filter(|(_, input)| spendable_with_change(input, &options))
+        .filter(|(_, input)| meets_min_efficiency(input, &options))
+        .filter(|(_, input)| !is_dust(input, &options))
+        .collect();
+    // `None` sorts below any sequence, so reversing puts it last
+    sorted_inputs.sort_by_key(|(_, input)| core::cmp::Reverse(input.creation_sequence));
+
+    select_in_order(inputs, &options, sorted_inputs, Algorithm::RecentChange)
+}
@@ -0,0 +1,29 @@
+use alloc::vec::Vec;
+
+use super::select_in_order;
+use crate::{
+    types::{Algorithm, CoinSelectionOpt, OutputGroup, SelectionError, SelectionOutput},
+    utils::{effective_value, is_dust, meets_min_efficiency, spendable_with_change},
+};
+
+/// Perform Coinselection spending the inputs of lowest effective value first, consolidating dust
+/// while feerates are below the `long_term_feerate`. Uneconomical inputs, which cost at least
+/// their value to spend, are left out.
+/// Return InsufficientFunds, if all inputs do not cover the target.
+#[must_use = "the selection result tells which inputs to spend"]
+pub fn select_coin_smallest_first(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+) -> Result<SelectionOutput, SelectionError> {
+    options.validate()?;
+    let mut sorted_inputs: Vec<_> = inputs
+        .iter()
+        .enumerate()
+        .filter(|(_, input)| spendable_with_change(input, &options))
+        .filter(|(_, input)| meets_min_efficiency(input, &options))
+        .filter(|(_, input)| !is_dust(input, &options))
+        .collect();
+    sorted_inputs.sort_by_key(|(_, input)| effective_value(input, &options));
+
+    select_in_order(inputs, &options, sorted_inputs, Algorithm::SmallestFirst)
+}
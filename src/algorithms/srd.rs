@@ -0,0 +1,84 @@
+use alloc::vec::Vec;
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    types::{
+        Algorithm, CoinSelectionOpt, InputIndex, OutputGroup, SelectionError, SelectionOutput,
+    },
+    utils::{
+        accumulate, finalize_selection, is_dust, is_forbidden, meets_min_efficiency,
+        required_value, spendable_with_change, transaction_fee,
+    },
+};
+
+/// Perform Coinselection via Single Random Draw.
+/// Inputs are drawn in random order until their effective value covers the target, the fee for
+/// `base_weight` and `drain_weight`, and `min_drain_value` of change, so that the change output
+/// is never dust.
+/// Return InsufficientFunds, if all inputs do not cover the target.
+#[must_use = "the selection result tells which inputs to spend"]
+pub fn select_coin_srd(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+    rng: &mut impl Rng,
+) -> Result<SelectionOutput, SelectionError> {
+    options.validate()?;
+    srd(inputs, options, &[], rng)
+}
+
+/// Single Random Draw that skips any input forbidden alongside an already drawn one.
+pub(crate) fn srd<R: Rng>(
+    inputs: &[OutputGroup],
+    options: CoinSelectionOpt,
+    forbidden_pairs: &[(InputIndex, InputIndex)],
+    rng: &mut R,
+) -> Result<SelectionOutput, SelectionError> {
+    // Randomize the inputs order to simulate the random draw
+    // In out put we need to specify the indexes of the inputs in the given order
+    // So keep track of the indexes when randomiz ing the vec
+    let mut randomized_inputs: Vec<_> = inputs
+        .iter()
+        .enumerate()
+        .filter(|(_, input)| spendable_with_change(input, &options))
+        .filter(|(_, input)| meets_min_efficiency(input, &options))
+        .filter(|(_, input)| !is_dust(input, &options))
+        .collect();
+
+    // Randomize the inputs order to simulate the random draw
+    randomized_inputs.shuffle(rng);
+
+    let (mut accumulated_value, mut accumulated_weight) = options.anchor.unwrap_or((0, 0));
+    let mut selected_inputs = Vec::new();
+    let mut has_segwit = false;
+
+    for (index, input) in randomized_inputs {
+        if accumulated_value
+            >= required_value(&options, transaction_fee(&options, accumulated_weight)?)?
+        {
+            break;
+        }
+        if is_forbidden(index, &selected_inputs, forbidden_pairs) {
+            continue;
+        }
+        selected_inputs.push(index);
+        (accumulated_value, accumulated_weight) = accumulate(
+            (accumulated_value, accumulated_weight),
+            input,
+            &mut has_segwit,
+        )?;
+    }
+
+    if accumulated_value < required_value(&options, transaction_fee(&options, accumulated_weight)?)?
+    {
+        return Err(SelectionError::InsufficientFunds);
+    }
+    finalize_selection(
+        inputs,
+        selected_inputs,
+        &options,
+        accumulated_value,
+        accumulated_weight,
+        Algorithm::Srd,
+    )
+}
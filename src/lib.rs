@@ -0,0 +1,49 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! A blockchain-agnostic Rust Coinselection library
+//!
+//! The selectors only need `alloc` and an RNG passed in by the caller, so the crate builds for
+//! `no_std` targets with `--no-default-features`. The default `std` feature adds
+//! `SeedSource::Os`; `rayon`, which implies `std`, adds `select_coin_bnb_parallel`.
+
+extern crate alloc;
+
+mod algorithms;
+mod types;
+mod utils;
+
+#[cfg(feature = "rayon")]
+pub use algorithms::bnb::select_coin_bnb_parallel;
+pub use algorithms::{
+    anchor_topup::select_coin_anchor_topup,
+    bnb::{
+        bnb_solutions, changeless_solution_exists, select_coin_bnb, select_coin_bnb_deterministic,
+        select_coin_bnb_with_drain, select_coin_bnb_with_forbidden_pairs,
+        select_coin_bnb_with_preselected, MatchParameters,
+    },
+    coin_age::select_coin_coin_age,
+    fifo::select_coin_fifo,
+    knapsack::select_coin_knapsack,
+    largest_first::select_coin_largest_first,
+    lowestlarger::{select_coin_lowestlarger, select_coin_single_lowest_larger},
+    min_risk::select_coin_min_risk,
+    optimal::select_coin_optimal,
+    recent_change::select_coin_recent_change,
+    select_coin, select_coin_from_pools, select_coin_rbf, select_coin_with_confirmed_input,
+    select_coin_with_forced,
+    smallest_first::select_coin_smallest_first,
+    srd::select_coin_srd,
+};
+pub use types::{
+    Algorithm, Amount, CoinSelectionOpt, CoinSelectionOptBuilder, DecodeError, DustError,
+    ExcessStrategy, FeeRate, InputIndex, OptionError, OutputGroup, Pool, SeedSource,
+    SelectionError, SelectionOutput, SelectionReason, SubDustPolicy, WasteMetric, WasteModel,
+};
+pub use utils::{
+    break_even_feerate, compare_selections, compute_waste, dust_input_count,
+    effective_value_checked, estimate_change, fee_rate_achieved, output_groups_from,
+    partition_selection, quality_score, select_coin_in, target_from_fraction, QualityWeights,
+};
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,3125 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::*;
+use crate::{
+    algorithms::{bnb::*, optimize_subset, srd::*},
+    utils::*,
+};
+
+fn setup_basic_output_groups() -> Vec<OutputGroup> {
+    vec![
+        OutputGroup {
+            value: 1000,
+            weight: 100,
+            input_count: 1,
+            is_segwit: false,
+            creation_sequence: None,
+            changeless_only: false,
+            pool: Pool::Spending,
+        },
+        OutputGroup {
+            value: 2000,
+            weight: 200,
+            input_count: 1,
+            is_segwit: false,
+            creation_sequence: None,
+            changeless_only: false,
+            pool: Pool::Spending,
+        },
+        OutputGroup {
+            value: 3000,
+            weight: 300,
+            input_count: 1,
+            is_segwit: false,
+            creation_sequence: None,
+            changeless_only: false,
+            pool: Pool::Spending,
+        },
+    ]
+}
+/// The 8-UTXO sample set, with the 400 and 600 value dust coins.
+fn setup_bnb_sample_output_groups() -> Vec<OutputGroup> {
+    OutputGroup::from_utxos(&[
+        (55000, 500, false),
+        (400, 200, false),
+        (40000, 300, false),
+        (25000, 100, false),
+        (35000, 150, false),
+        (600, 250, false),
+        (30000, 120, false),
+        (5000, 50, false),
+    ])
+}
+
+fn setup_output_groups_withsequence() -> Vec<OutputGroup> {
+    vec![
+        OutputGroup {
+            value: 1000,
+            weight: 100,
+            input_count: 1,
+            is_segwit: false,
+            creation_sequence: Some(1),
+            changeless_only: false,
+            pool: Pool::Spending,
+        },
+        OutputGroup {
+            value: 2000,
+            weight: 200,
+            input_count: 1,
+            is_segwit: false,
+            creation_sequence: Some(5000),
+            changeless_only: false,
+            pool: Pool::Spending,
+        },
+        OutputGroup {
+            value: 3000,
+            weight: 300,
+            input_count: 1,
+            is_segwit: false,
+            creation_sequence: Some(1001),
+            changeless_only: false,
+            pool: Pool::Spending,
+        },
+    ]
+}
+fn setup_lowestlarger_output_groups() -> Vec<OutputGroup> {
+    vec![
+        OutputGroup {
+            value: 100,
+            weight: 100,
+            input_count: 1,
+            is_segwit: false,
+            creation_sequence: None,
+            changeless_only: false,
+            pool: Pool::Spending,
+        },
+        OutputGroup {
+            value: 1500,
+            weight: 200,
+            input_count: 1,
+            is_segwit: false,
+            creation_sequence: None,
+            changeless_only: false,
+            pool: Pool::Spending,
+        },
+        OutputGroup {
+            value: 3400,
+            weight: 300,
+            input_count: 1,
+            is_segwit: false,
+            creation_sequence: None,
+            changeless_only: false,
+            pool: Pool::Spending,
+        },
+        OutputGroup {
+            value: 2200,
+            weight: 150,
+            input_count: 1,
+            is_segwit: false,
+            creation_sequence: None,
+            changeless_only: false,
+            pool: Pool::Spending,
+        },
+        OutputGroup {
+            value: 1190,
+            weight: 200,
+            input_count: 1,
+            is_segwit: false,
+            creation_sequence: None,
+            changeless_only: false,
+            pool: Pool::Spending,
+        },
+        OutputGroup {
+            value: 3300,
+            weight: 100,
+            input_count: 1,
+            is_segwit: false,
+            creation_sequence: None,
+            changeless_only: false,
+            pool: Pool::Spending,
+        },
+        OutputGroup {
+            value: 1000,
+            weight: 190,
+            input_count: 1,
+            is_segwit: false,
+            creation_sequence: None,
+            changeless_only: false,
+            pool: Pool::Spending,
+        },
+        OutputGroup {
+            value: 2000,
+            weight: 210,
+            input_count: 1,
+            is_segwit: false,
+            creation_sequence: None,
+            changeless_only: false,
+            pool: Pool::Spending,
+        },
+        OutputGroup {
+            value: 3000,
+            weight: 300,
+            input_count: 1,
+            is_segwit: false,
+            creation_sequence: None,
+            changeless_only: false,
+            pool: Pool::Spending,
+        },
+        OutputGroup {
+            value: 2250,
+            weight: 250,
+            input_count: 1,
+            is_segwit: false,
+            creation_sequence: None,
+            changeless_only: false,
+            pool: Pool::Spending,
+        },
+        OutputGroup {
+            value: 190,
+            weight: 220,
+            input_count: 1,
+            is_segwit: false,
+            creation_sequence: None,
+            changeless_only: false,
+            pool: Pool::Spending,
+        },
+        OutputGroup {
+            value: 1750,
+            weight: 170,
+            input_count: 1,
+            is_segwit: false,
+            creation_sequence: None,
+            changeless_only: false,
+            pool: Pool::Spending,
+        },
+    ]
+}
+
+fn setup_options(target_value: u64) -> CoinSelectionOpt {
+    CoinSelectionOptBuilder::new(target_value, FeeRate::from_sat_per_kwu(500)) // Simplified feerate
+        .base_weight(10)
+        .drain_weight(50)
+        .drain_cost(10)
+        .cost_per_input(20)
+        .cost_per_output(10)
+        .min_drain_value(500)
+        .build()
+}
+
+#[test]
+fn test_bnb_basic() {
+    // Perform BNB selection of set of test values.
+    let values = [
+        OutputGroup {
+            value: 2000,
+            weight: 200,
+            input_count: 1,
+            is_segwit: false,
+            creation_sequence: Some(1),
+            changeless_only: false,
+            pool: Pool::Spending,
+        },
+        OutputGroup {
+            value: 5000000,
+            weight: 200,
+            input_count: 1,
+            is_segwit: false,
+            creation_sequence: Some(5000),
+            changeless_only: false,
+            pool: Pool::Spending,
+        },
+        OutputGroup {
+            value: 9000000,
+            weight: 300,
+            input_count: 1,
+            is_segwit: false,
+            creation_sequence: Some(1001),
+            changeless_only: false,
+            pool: Pool::Spending,
+        },
+        OutputGroup {
+            value: 270,
+            weight: 10,
+            input_count: 1,
+            is_segwit: false,
+            creation_sequence: Some(1000),
+            changeless_only: false,
+            pool: Pool::Spending,
+        },
+    ];
+    let opt = setup_options(14000000);
+    let ans = select_coin_bnb(&values, opt, &mut rand::thread_rng());
+    assert!(ans.is_ok());
+    assert!(!ans.unwrap().selected_inputs.contains(&InputIndex(0)));
+    // as 10000000 should not be included in the selection
+}
+
+#[test]
+fn test_bnb_exact_one_solution() {
+    let values = setup_bnb_sample_output_groups();
+
+    // Adjust the target value to ensure it tests for multiple valid solutions
+    let opt = setup_options(5730);
+    let ans = select_coin_bnb(&values, opt, &mut rand::thread_rng());
+    if let Ok(selection_output) = ans {
+        let expected_solution = vec![7, 5, 1];
+        assert_eq!(
+            selection_output.selected_inputs, expected_solution,
+            "Expected solution {:?}, but got {:?}",
+            expected_solution, selection_output.selected_inputs
+        );
+    } else {
+        panic!("Failed to find a solution");
+    }
+}
+
+#[test]
+fn test_srd_multiple_solutions() {
+    // Define the test values
+    let values = [
+        OutputGroup { value: 55000, weight: 500, input_count: 1, is_segwit: false, creation_sequence: None, changeless_only: false, pool: Pool::Spending },
+        OutputGroup { value: 40000, weight: 200, input_count: 1, is_segwit: false, creation_sequence: None, changeless_only: false, pool: Pool::Spending },
+        OutputGroup { value: 40000, weight: 300, input_count: 1, is_segwit: false, creation_sequence: None, changeless_only: false, pool: Pool::Spending },
+        OutputGroup { value: 25000, weight: 100, input_count: 1, is_segwit: false, creation_sequence: None, changeless_only: false, pool: Pool::Spending },
+        OutputGroup { value: 35000, weight: 150, input_count: 1, is_segwit: false, creation_sequence: None, changeless_only: false, pool: Pool::Spending },
+        OutputGroup { value: 60000, weight: 250, input_count: 1, is_segwit: false, creation_sequence: None, changeless_only: false, pool: Pool::Spending },
+        OutputGroup { value: 30000, weight: 120, input_count: 1, is_segwit: false, creation_sequence: None, changeless_only: false, pool: Pool::Spending },
+        OutputGroup { value: 5000, weight: 50, input_count: 1, is_segwit: false, creation_sequence: None, changeless_only: false, pool: Pool::Spending },
+    ];
+
+    // Adjust the target value to ensure it's achievable
+    let opt = setup_options(93000);
+
+    // Define the valid combinations
+    let valid_combinations = [vec![0, 1], vec![0, 2], vec![1, 3, 6], vec![2, 3, 6]];
+    let mut found_solutions = Vec::new();
+    let mut rng = StdRng::seed_from_u64(1);
+
+    println!("Starting BnB selection with target value: {}", opt.target_value);
+
+    // Run the BnB selection algorithm multiple times to find different solutions
+    for i in 0..1000 {
+        let ans = select_coin_srd(&values, opt, &mut rng);
+        println!("Iteration {}: Result = {:?}", i, ans);
+
+        if let Ok(selection_output) = ans {
+            let selected_inputs = selection_output.selected_inputs;
+            let total_value: u64 = selected_inputs.iter().map(|&i| values[i].value).sum();
+            println!("Selected inputs: {:?}, Total value: {}", selected_inputs, total_value);
+
+            // Check if the selected inputs match any of the valid combinations
+            let selected_inputs: Vec<usize> = selected_inputs.into_iter().map(usize::from).collect();
+            if valid_combinations.contains(&selected_inputs) && !found_solutions.contains(&selected_inputs) {
+                found_solutions.push(selected_inputs.clone());
+                println!("Found new solution: {:?}", selected_inputs);
+            }
+        }
+
+        // Print progress every 100 iterations
+        if (i + 1) % 100 == 0 {
+            println!("Completed {} iterations. Current found solutions: {:?}", i + 1, found_solutions);
+        }
+
+        // Break early if all solutions are found
+        if found_solutions.len() == valid_combinations.len() {
+            println!("All solutions found after {} iterations", i + 1);
+            break;
+        }
+    }
+
+    // Ensure that all valid combinations are found
+    assert!(
+        found_solutions.len() == valid_combinations.len(),
+        "Expected all valid combinations, but found fewer: found {}, expected {}",
+        found_solutions.len(),
+        valid_combinations.len()
+    );
+
+    println!("Final found solutions: {:?}", found_solutions);
+}
+
+#[test]
+fn test_bnb_no_solutions() {
+    let inputs = setup_basic_output_groups();
+    let options = setup_options(7000); // Set a target value higher than the sum of all inputs
+    let result = select_coin_bnb(&inputs, options, &mut rand::thread_rng());
+    assert!(matches!(result, Err(SelectionError::InsufficientFunds)));
+}
+
+fn test_successful_selection() {
+    let mut inputs = setup_basic_output_groups();
+    let mut options = setup_options(2500);
+    let mut result = select_coin_srd(&inputs, options, &mut rand::thread_rng());
+    assert!(result.is_ok());
+    let mut selection_output = result.unwrap();
+    assert!(!selection_output.selected_inputs.is_empty());
+
+    inputs = setup_output_groups_withsequence();
+    options = setup_options(500);
+    result = select_coin_fifo(&inputs, options);
+    assert!(result.is_ok());
+    selection_output = result.unwrap();
+    assert!(!selection_output.selected_inputs.is_empty());
+}
+
+fn test_insufficient_funds() {
+    let inputs = setup_basic_output_groups();
+    let options = setup_options(7000); // Set a target value higher than the sum of all inputs
+    let result = select_coin_srd(&inputs, options, &mut rand::thread_rng());
+    assert!(matches!(result, Err(SelectionError::InsufficientFunds)));
+}
+
+
+#[test]
+fn test_srd() {
+    test_successful_selection();
+    test_insufficient_funds();
+}
+
+#[test]
+fn test_knapsack() {
+    // Perform Knapsack selection of set of test values.
+    let inputs = OutputGroup::from_utxos(&[
+        (1000, 20, false),
+        (2000, 20, false),
+        (3000, 20, false),
+        (4000, 20, false),
+        (60000, 20, false),
+    ]);
+    let mut rng = rand::thread_rng();
+    let mut options = setup_options(5000);
+    options.excess_strategy = ExcessStrategy::ToFee;
+
+    // The smaller coins reach 5000 + 500 with the least excess as 990 + 1990 + 2990.
+    let result = select_coin_knapsack(&inputs, options, &mut rng).unwrap();
+    let mut selected = result.selected_inputs.clone();
+    selected.sort();
+    assert_eq!(selected, vec![0, 1, 2]);
+    assert_eq!(result.chosen_algorithm, Algorithm::Knapsack);
+
+    // An exact match, paying for the change and the 30 sat fee of the base and drain weights, is
+    // spent alone.
+    let result = select_coin_knapsack(&inputs, setup_options(3990 - 500 - 30), &mut rng).unwrap();
+    assert_eq!(result.selected_inputs, vec![3]);
+
+    // Without enough smaller coins, the lowest larger coin is spent.
+    let result = select_coin_knapsack(&inputs[2..], setup_options(9000), &mut rng).unwrap();
+    assert_eq!(result.selected_inputs, vec![2]);
+
+    assert!(matches!(
+        select_coin_knapsack(&inputs, setup_options(100000), &mut rng),
+        Err(SelectionError::InsufficientFunds)
+    ));
+}
+
+#[test]
+fn test_bnb() {
+    test_bnb_basic();
+    test_bnb_exact_one_solution();
+    // test_bnb_multiple_solutions();
+    test_bnb_no_solutions();
+}
+
+#[test]
+fn test_fifo() {
+    test_successful_selection();
+    test_insufficient_funds();
+}
+
+#[test]
+fn test_lowestlarger_successful() {
+    let inputs = setup_lowestlarger_output_groups();
+    let options = setup_options(20000);
+    let result = select_coin_lowestlarger(&inputs, options);
+    assert!(result.is_ok());
+    let selection_output = result.unwrap();
+    assert!(!selection_output.selected_inputs.is_empty());
+}
+
+#[test]
+fn test_lowestlarger_insufficient() {
+    let inputs = setup_lowestlarger_output_groups();
+    let options = setup_options(40000);
+    let result = select_coin_lowestlarger(&inputs, options);
+    assert!(matches!(result, Err(SelectionError::InsufficientFunds)));
+}
+
+#[test]
+fn test_match_parameters_from_options() {
+    let options = setup_options(5730);
+    let match_parameters = MatchParameters::from_options(&options).unwrap();
+    // 5730 target + ceil(10 * 0.5) base fee + 10 cost_per_output
+    assert_eq!(match_parameters.target_for_match(), 5745);
+    // 20 cost_per_input + 10 cost_per_output
+    assert_eq!(match_parameters.match_range(), 30);
+    assert_eq!(
+        match_parameters.target_feerate(),
+        FeeRate::from_sat_per_kwu(500)
+    );
+}
+
+#[test]
+fn test_fifo_with_anchor() {
+    let inputs = setup_output_groups_withsequence();
+    let mut options = setup_options(2000);
+    let without_anchor = select_coin_fifo(&inputs, options).unwrap();
+    assert_eq!(without_anchor.selected_inputs, vec![0, 2]);
+
+    // The anchor from the parent already carries most of the package value.
+    options.anchor = Some((2000, 100));
+    let with_anchor = select_coin_fifo(&inputs, options).unwrap();
+    assert_eq!(with_anchor.selected_inputs, vec![0]);
+}
+
+#[test]
+fn test_match_parameters_overflow() {
+    let inputs = setup_basic_output_groups();
+    let mut options = setup_options(2500);
+    options.cost_per_input = u64::MAX - 5;
+    options.cost_per_output = 10;
+    assert!(matches!(
+        MatchParameters::from_options(&options),
+        Err(SelectionError::Overflow)
+    ));
+    let result = select_coin_bnb(&inputs, options, &mut rand::thread_rng());
+    assert!(matches!(result, Err(SelectionError::Overflow)));
+}
+
+/// Generate a wallet-like portfolio whose values follow a log-normal distribution.
+fn generate_portfolio(rng: &mut impl Rng, size: usize) -> Vec<OutputGroup> {
+    (0..size)
+        .map(|i| {
+            // Box-Muller transform from two uniform samples to a standard normal one.
+            let u1: f64 = rng.gen_range(f64::EPSILON..1.0);
+            let u2: f64 = rng.gen();
+            let normal = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+            OutputGroup {
+                value: (10.0 + 1.5 * normal).exp() as u64,
+                weight: rng.gen_range(150..700),
+                input_count: 1,
+                is_segwit: rng.gen_bool(0.8),
+                creation_sequence: Some(i as u32),
+                changeless_only: false,
+                pool: Pool::Spending,
+            }
+        })
+        .collect()
+}
+
+/// Assert a selection covers its fee-adjusted target, or that a failure is genuinely underfunded.
+fn assert_selection_sound(
+    name: &str,
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+    result: Result<SelectionOutput, SelectionError>,
+) {
+    let target_value = options.target_value;
+    let feerate = options.target_feerate;
+    match result {
+        Ok(selection) => {
+            let (value, weight) =
+                selection_totals(inputs, &selection.selected_inputs, options).unwrap();
+            let fee = calculate_fee(weight + options.base_weight, feerate);
+            assert!(
+                value >= target_value + fee,
+                "{name} under-covered target {target_value} at feerate {feerate:?}"
+            );
+            if let Some(change_value) = selection.change_value {
+                let fee =
+                    calculate_fee(weight + options.base_weight + options.drain_weight, feerate);
+                assert!(
+                    value >= target_value + change_value + fee,
+                    "{name} overpaid the change for target {target_value} at feerate {feerate:?}"
+                );
+            }
+        }
+        Err(SelectionError::InsufficientFunds | SelectionError::InsufficientFundsForFee) => {
+            // Only inputs worth spending at this feerate could have helped.
+            let spendable: Vec<usize> = (0..inputs.len())
+                .filter(|&i| is_economical(&inputs[i], options))
+                .collect();
+            let (value, weight) = selection_totals(inputs, &spendable, options).unwrap();
+            let fee = calculate_fee(weight + options.base_weight + options.drain_weight, feerate);
+            assert!(
+                value < target_value + options.min_drain_value + fee,
+                "{name} reported insufficient funds for target {target_value} at feerate {feerate:?}"
+            );
+        }
+        Err(err) => panic!("{name} failed unexpectedly: {err:?}"),
+    }
+}
+
+#[test]
+fn test_stress_generated_portfolio() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let mut portfolio_rng = StdRng::seed_from_u64(0x5eed);
+    let inputs = generate_portfolio(&mut portfolio_rng, 500);
+    let total_value: u64 = inputs.iter().map(|input| input.value).sum();
+
+    for target_value in [1_000, 50_000, 1_000_000, total_value / 2, total_value * 2] {
+        for target_feerate in [250, 1000, 5000, 25000] {
+            let mut options = setup_options(target_value);
+            options.target_feerate = FeeRate::from_sat_per_kwu(target_feerate);
+            // Bound the BnB search, which would otherwise explore a set this size for long.
+            options.bnb_tries = Some(10_000);
+            let mut rng = StdRng::seed_from_u64(target_value ^ target_feerate);
+            let fifo = select_coin_fifo(&inputs, options);
+            assert_selection_sound("fifo", &inputs, &options, fifo);
+            let lowestlarger = select_coin_lowestlarger(&inputs, options);
+            assert_selection_sound("lowestlarger", &inputs, &options, lowestlarger);
+            let srd = select_coin_srd(&inputs, options, &mut rng);
+            assert_selection_sound("srd", &inputs, &options, srd);
+            let bnb = select_coin_bnb(&inputs, options, &mut rng);
+            assert_selection_sound("bnb", &inputs, &options, bnb);
+            let dispatched = select_coin(&inputs, options, &mut rng);
+            assert_selection_sound("select_coin", &inputs, &options, dispatched);
+        }
+    }
+}
+
+#[test]
+fn test_output_group_from_utxos() {
+    let single = OutputGroup::single(1000, 100, true);
+    assert_eq!(single.input_count, 1);
+    assert!(single.is_segwit);
+    assert_eq!(single.creation_sequence, None);
+
+    let inputs =
+        OutputGroup::from_utxos(&[(1000, 100, false), (2000, 200, true), (3000, 300, false)]);
+    assert_eq!(inputs.len(), 3);
+    assert_eq!(inputs[1].value, 2000);
+    assert_eq!(inputs[1].weight, 200);
+    assert!(inputs[1].is_segwit);
+
+    let options = setup_options(2500);
+    let selection = select_coin_lowestlarger(&inputs, options).unwrap();
+    let selected_value: u64 = selection
+        .selected_inputs
+        .iter()
+        .map(|&i| inputs[i].value)
+        .sum();
+    assert!(selected_value >= options.target_value + options.min_drain_value);
+}
+
+#[test]
+fn test_bnb_forbidden_pairs() {
+    // Both {0, 1} and {2, 3} match the target, but 0 and 1 must not be spent together.
+    let inputs = OutputGroup::from_utxos(&[
+        (6000, 20, false),
+        (4000, 20, false),
+        (7000, 20, false),
+        (3000, 20, false),
+    ]);
+    let options = setup_options(9950);
+    for _ in 0..50 {
+        let mut selected = select_coin_bnb_with_forbidden_pairs(
+            &inputs,
+            options,
+            &[(InputIndex(0), InputIndex(1))],
+            &mut rand::thread_rng(),
+        )
+        .unwrap()
+        .selected_inputs;
+        selected.sort();
+        assert_eq!(selected, vec![2, 3]);
+    }
+}
+
+#[test]
+fn test_selection_output_encode_round_trip() {
+    let selection = SelectionOutput {
+        selected_inputs: [0, 7, 128, 300, 70000].map(InputIndex).to_vec(),
+        waste: WasteMetric(123_456),
+        audit: None,
+        chosen_algorithm: Algorithm::Srd,
+        change_value: Some(5000),
+        iterations: 0,
+    };
+    let bytes = selection.encode();
+    // count + 5 indices (1, 1, 2, 2, 3 bytes) + waste (3 bytes) + algorithm + change (1 + 2 bytes)
+    assert_eq!(bytes.len(), 1 + 9 + 3 + 1 + 3);
+
+    let decoded = SelectionOutput::decode(&bytes).unwrap();
+    assert_eq!(decoded.selected_inputs, selection.selected_inputs);
+    assert_eq!(decoded.waste.0, selection.waste.0);
+    assert_eq!(decoded.chosen_algorithm, Algorithm::Srd);
+    assert_eq!(decoded.change_value, Some(5000));
+
+    assert_eq!(
+        SelectionOutput::decode(&bytes[..bytes.len() - 1]).unwrap_err(),
+        DecodeError::UnexpectedEnd
+    );
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(
+        SelectionOutput::decode(&trailing).unwrap_err(),
+        DecodeError::TrailingBytes
+    );
+    let mut invalid_flag = bytes[..bytes.len() - 3].to_vec();
+    invalid_flag.push(2);
+    assert_eq!(
+        SelectionOutput::decode(&invalid_flag).unwrap_err(),
+        DecodeError::InvalidChangeFlag
+    );
+}
+
+#[test]
+fn test_fifo_max_excess_to_fee() {
+    let inputs = setup_output_groups_withsequence();
+    let mut options = setup_options(3750);
+    options.max_excess_to_fee = Some(100);
+    // 4000 selected, 205 fee: the 45 overshoot is paid as fee instead of creating change.
+    let selection = select_coin_fifo(&inputs, options).unwrap();
+    assert_eq!(selection.selected_inputs, vec![0, 2]);
+    assert_eq!(selection.waste.0, 45);
+
+    // 4000 selected, 205 fee: a 895 overshoot is too wasteful to give away.
+    options.target_value = 2900;
+    let result = select_coin_fifo(&inputs, options);
+    assert!(matches!(result, Err(SelectionError::NoSolutionFound)));
+}
+
+#[test]
+fn test_optimize_subset() {
+    let inputs = setup_lowestlarger_output_groups();
+    let mut options = setup_options(5000);
+    options.excess_strategy = ExcessStrategy::ToFee;
+    let everything: Vec<usize> = (0..inputs.len()).collect();
+    let initial_waste = compute_waste(&inputs, &everything, &options);
+
+    let mut rng = StdRng::seed_from_u64(210);
+    let mut improved = 0;
+    for _ in 0..20 {
+        let optimized = optimize_subset(&inputs, &everything, &options, &mut rng, 200);
+        let (value, weight) = selection_totals(&inputs, &optimized, &options).unwrap();
+        assert!(covers_target(&options, value, weight));
+        let waste = compute_waste(&inputs, &optimized, &options);
+        assert!(waste <= initial_waste);
+        if waste < initial_waste {
+            improved += 1;
+        }
+    }
+    assert!(improved >= 15, "improved {improved} of 20");
+}
+
+#[test]
+fn test_feerate_from_sat_per_kwu() {
+    let feerate = FeeRate::from_sat_per_kwu(17);
+    assert_eq!(feerate.as_sat_per_kwu(), 17);
+    assert_eq!(feerate.fee_for_weight(10000), 170);
+    assert_eq!(feerate.fee_for_weight(561), 10);
+    assert_eq!(calculate_fee(10000, feerate), 170);
+}
+
+#[test]
+fn test_selection_audit() {
+    let inputs = setup_output_groups_withsequence();
+    let mut options = setup_options(2000);
+    assert!(select_coin_fifo(&inputs, options).unwrap().audit.is_none());
+
+    options.with_audit = true;
+    let selection =
+        select_coin_with_forced(&inputs, options, &[InputIndex(1)], select_coin_fifo).unwrap();
+    assert_eq!(selection.selected_inputs, vec![1, 0]);
+    assert_eq!(
+        selection.audit.unwrap(),
+        vec![
+            (InputIndex(1), SelectionReason::Forced),
+            (InputIndex(0), SelectionReason::NeededToReachTarget)
+        ]
+    );
+}
+
+#[test]
+fn test_coin_age_closest_solution() {
+    // Any single input covers the target, all with the same value and weight.
+    let inputs: Vec<OutputGroup> = [0, 10, 4]
+        .iter()
+        .map(|&sequence| OutputGroup {
+            creation_sequence: Some(sequence),
+            changeless_only: false,
+            pool: Pool::Spending,
+            ..OutputGroup::single(3000, 100, false)
+        })
+        .collect();
+    let mut options = setup_options(2000);
+    let mut rng = rand::thread_rng();
+
+    // Ages are 10, 0 and 6 respectively.
+    for (target_coin_age, expected) in [(10, 0), (0, 1), (5, 2)] {
+        options.target_coin_age = Some(target_coin_age);
+        let selection = select_coin_coin_age(&inputs, options, &mut rng).unwrap();
+        assert_eq!(selection.selected_inputs, vec![expected]);
+    }
+}
+
+#[test]
+fn test_bnb_exact_total_fast_path() {
+    // Effective values 990 + 1990 + 2990 = 5970 = 5955 target + 5 base fee + 10 per output.
+    let mut inputs =
+        OutputGroup::from_utxos(&[(1000, 20, false), (2000, 20, false), (3000, 20, false)]);
+    // Dust with no effective value is left out of the whole-set selection.
+    inputs.push(OutputGroup::single(5, 20, false));
+    let options = setup_options(5955);
+    let selection = select_coin_bnb(&inputs, options, &mut rand::thread_rng()).unwrap();
+    // The fast path keeps the input order, where the search would follow descending value.
+    assert_eq!(selection.selected_inputs, vec![0, 1, 2]);
+}
+
+#[test]
+fn test_bnb_max_depth() {
+    // Both {0, 2} and {0, 1, 3} match, but only the former uses the two largest inputs.
+    let inputs = OutputGroup::from_utxos(&[
+        (6000, 20, false),
+        (4000, 20, false),
+        (7000, 20, false),
+        (3000, 20, false),
+    ]);
+    let mut options = setup_options(12950);
+    options.max_depth = Some(2);
+    for _ in 0..50 {
+        let mut selected = select_coin_bnb(&inputs, options, &mut rand::thread_rng())
+            .unwrap()
+            .selected_inputs;
+        selected.sort();
+        assert_eq!(selected, vec![0, 2]);
+    }
+}
+
+#[test]
+fn test_quality_score_monotonic_in_waste() {
+    let inputs = setup_basic_output_groups();
+    let options = setup_options(2500);
+    let weights = QualityWeights {
+        waste: 1.0,
+        input_count: 0.5,
+        privacy: 0.25,
+    };
+    let scores: Vec<f64> = [0, 10, 100, 1000]
+        .iter()
+        .map(|&waste| {
+            let output = SelectionOutput {
+                selected_inputs: vec![InputIndex(0), InputIndex(2)],
+                waste: WasteMetric(waste),
+                audit: None,
+                chosen_algorithm: Algorithm::Bnb,
+                change_value: None,
+                iterations: 0,
+            };
+            quality_score(&output, &inputs, &options, weights)
+        })
+        .collect();
+    assert!(scores.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn test_bnb_changeless_does_not_fall_back() {
+    let inputs = OutputGroup::from_utxos(&[
+        (6000, 20, false),
+        (4000, 20, false),
+        (7000, 20, false),
+        (3000, 20, false),
+    ]);
+    let mut rng = rand::thread_rng();
+
+    let mut selected = select_coin_bnb_changeless(&inputs, setup_options(9950), &mut rng)
+        .unwrap()
+        .selected_inputs;
+    selected.sort();
+    assert!(selected == vec![0, 1] || selected == vec![2, 3]);
+
+    // No subset lands in the match window, where select_coin_bnb would fall back to SRD.
+    let options = setup_options(9000);
+    let result = select_coin_bnb_changeless(&inputs, options, &mut rng);
+    assert!(matches!(result, Err(SelectionError::NoSolutionFound)));
+    assert!(select_coin_bnb(&inputs, options, &mut rng).is_ok());
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_calculate_waste_clamps_negative_intermediates() {
+    let mut options = setup_options(2500);
+    options.excess_strategy = ExcessStrategy::ToFee;
+    options.waste_model = WasteModel::Legacy;
+    // Spending now at 500 sat/kWU is cheaper than the 10000 sat/kWU long-term feerate.
+    options.long_term_feerate = Some(FeeRate::from_sat_per_kwu(10000));
+    // The inputs weigh 490, 500 with the base weight, so the fee is 250.
+    let waste = calculate_waste(&[1, 2], &options, 5000, 490, false);
+    assert_eq!(waste.unwrap(), 5000 - 2500 - 250);
+
+    // The accumulated value doesn't even cover the fee: the excess counts as zero.
+    let waste = calculate_waste(&[1, 2], &options, 2600, 490, false);
+    assert_eq!(waste.unwrap(), 0);
+
+    // The consolidated model offsets the excess by the negative timing cost instead.
+    options.waste_model = WasteModel::Consolidated;
+    let waste = calculate_waste(&[1, 2], &options, 5000, 490, false);
+    assert_eq!(waste.unwrap(), 250 - 5000 + 2250);
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_calculate_waste_overflow() {
+    let mut options = setup_options(0);
+    options.long_term_feerate = Some(FeeRate::from_sat_per_kwu(0));
+    // The fee for 1000 weight units with the base weight is u64::MAX, plus the drain cost.
+    options.target_feerate = FeeRate::from_sat_per_kwu(u64::MAX);
+    let waste = calculate_waste(&[0], &options, u64::MAX, 990, true);
+    assert!(matches!(waste, Err(SelectionError::Overflow)));
+
+    options.excess_strategy = ExcessStrategy::ToFee;
+    options.waste_model = WasteModel::Legacy;
+    let waste = calculate_waste(&[0], &options, u64::MAX, 990, false);
+    assert!(matches!(waste, Err(SelectionError::Overflow)));
+
+    options.base_weight = u32::MAX;
+    let waste = calculate_waste(&[0], &options, 0, 100, false);
+    assert!(matches!(waste, Err(SelectionError::Overflow)));
+}
+
+#[test]
+fn test_fee_rate_achieved() {
+    let inputs = setup_basic_output_groups();
+    let options = setup_options(2500);
+    let output = SelectionOutput {
+        selected_inputs: vec![InputIndex(2)],
+        waste: WasteMetric(0),
+        audit: None,
+        chosen_algorithm: Algorithm::Bnb,
+        change_value: None,
+        iterations: 0,
+    };
+    // Changeless: the 500 excess is all fee, over 10 base + 300 input weight.
+    let changeless = fee_rate_achieved(&output, &inputs, &options, false);
+    // 500 / 310 sat/WU, rounded down to the sat/kWU.
+    assert_eq!(changeless, FeeRate::from_sat_per_kwu(1612));
+    // With change: the 50 drain weight is counted and the fee is ceil(360 * 0.5).
+    let with_change = fee_rate_achieved(&output, &inputs, &options, true);
+    assert_eq!(with_change, FeeRate::from_sat_per_kwu(500));
+}
+
+#[test]
+fn test_bnb_drops_uneconomical_inputs() {
+    let mut inputs = OutputGroup::from_utxos(&[(6000, 20, false), (4000, 20, false)]);
+    // At 500 sat/kWU, spending 200 WU costs 100 sats: more than any of these are worth.
+    inputs.extend((0..50).map(|value| OutputGroup::single(value, 200, false)));
+
+    let options = setup_options(9950);
+    let candidates = bnb_candidates(&inputs, &options);
+    let candidate_indices: Vec<usize> = candidates.iter().map(|(index, _)| *index).collect();
+    assert_eq!(candidate_indices, vec![0, 1]);
+    assert_eq!(inputs.len() - candidates.len(), 50);
+
+    let mut selected = select_coin_bnb(&inputs, options, &mut rand::thread_rng())
+        .unwrap()
+        .selected_inputs;
+    selected.sort();
+    assert_eq!(selected, vec![0, 1]);
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_break_even_feerate() {
+    let inputs = OutputGroup::from_utxos(&[
+        (3000, 290, false),
+        (1000, 200, false),
+        (1000, 200, false),
+        (1000, 190, false),
+    ]);
+    let mut options = setup_options(2000);
+    options.long_term_feerate = Some(FeeRate::from_sat_per_kwu(100));
+    options.waste_model = WasteModel::Legacy;
+    let selection = |selected_inputs: Vec<usize>| SelectionOutput {
+        selected_inputs: selected_inputs.into_iter().map(InputIndex).collect(),
+        waste: WasteMetric(0),
+        audit: None,
+        chosen_algorithm: Algorithm::Bnb,
+        change_value: None,
+        iterations: 0,
+    };
+    let fewer_larger = selection(vec![0]);
+    let more_smaller = selection(vec![1, 2, 3]);
+
+    // With the base weight the selections weigh 300 and 600: 100 * (1 * 300 - 3 * 600) / (300 - 600)
+    let break_even = break_even_feerate(&fewer_larger, &more_smaller, &inputs, &options);
+    assert_eq!(break_even, Some(FeeRate::from_sat_per_kwu(500)));
+    let consolidated = CoinSelectionOpt {
+        waste_model: WasteModel::Consolidated,
+        ..options
+    };
+    assert_eq!(
+        break_even_feerate(&fewer_larger, &more_smaller, &inputs, &consolidated),
+        Some(FeeRate::from_sat_per_kwu(100))
+    );
+
+    let waste_at = |selection: &SelectionOutput, sat_per_kwu: u64| {
+        let feerate = FeeRate::from_sat_per_kwu(sat_per_kwu);
+        let mut options = options;
+        options.target_feerate = feerate;
+        let (value, weight) =
+            selection_totals(&inputs, &selection.selected_inputs, &options).unwrap();
+        calculate_waste(&selection.selected_inputs, &options, value, weight, true).unwrap()
+    };
+    // Consolidating the smaller inputs wastes less below the break-even feerate, more above.
+    assert!(waste_at(&more_smaller, 400) < waste_at(&fewer_larger, 400));
+    assert_eq!(waste_at(&more_smaller, 500), waste_at(&fewer_larger, 500));
+    assert!(waste_at(&more_smaller, 600) > waste_at(&fewer_larger, 600));
+}
+
+#[test]
+fn test_target_from_fraction() {
+    let inputs = setup_output_groups_withsequence();
+    let mut options = setup_options(0);
+    options.min_drain_value = 0;
+    options.drain_cost = 0;
+    // (950 + 1900 + 2850) effective value - 5 base fee
+    assert_eq!(target_from_fraction(&inputs, &options, 1.0), 5695);
+    assert_eq!(target_from_fraction(&inputs, &options, 7.0), 5695);
+    assert_eq!(target_from_fraction(&inputs, &options, 0.0), 0);
+
+    // A sweep leaves no change to pay a drain output for.
+    options.target_value = target_from_fraction(&inputs, &options, 1.0);
+    options.excess_strategy = ExcessStrategy::ToFee;
+    let sweep = select_coin_fifo(&inputs, options).unwrap();
+    assert_eq!(sweep.selected_inputs.len(), inputs.len());
+
+    options.target_value = target_from_fraction(&inputs, &options, 0.5);
+    assert_eq!(options.target_value, 2847);
+    let partial = select_coin_fifo(&inputs, options).unwrap();
+    assert!(partial.selected_inputs.len() < inputs.len());
+}
+
+#[test]
+fn test_bnb_returns_first_match() {
+    // {0, 1} wastes 50 and {2, 3} wastes 100, and both land in the match window.
+    let inputs = OutputGroup::from_utxos(&[
+        (6000, 20, false),
+        (4000, 20, false),
+        (7000, 100, false),
+        (3050, 20, false),
+    ]);
+    let mut options = setup_options(9950);
+    options.excess_strategy = ExcessStrategy::ToFee;
+
+    let mut returned_wasteful_match = false;
+    for _ in 0..200 {
+        let selection = select_coin_bnb(&inputs, options, &mut rand::thread_rng()).unwrap();
+        let mut selected = selection.selected_inputs;
+        selected.sort();
+        assert!(selected == vec![0, 1] || selected == vec![2, 3]);
+        returned_wasteful_match |= selected == vec![2, 3];
+    }
+    assert!(returned_wasteful_match);
+}
+
+#[test]
+fn test_tie_resolution_reports_algorithm() {
+    let inputs = vec![
+        OutputGroup {
+            creation_sequence: Some(5),
+            changeless_only: false,
+            pool: Pool::Spending,
+            ..OutputGroup::single(5000, 100, false)
+        },
+        OutputGroup {
+            creation_sequence: Some(0),
+            changeless_only: false,
+            pool: Pool::Spending,
+            ..OutputGroup::single(1000, 100, false)
+        },
+        OutputGroup {
+            creation_sequence: Some(1),
+            changeless_only: false,
+            pool: Pool::Spending,
+            ..OutputGroup::single(1000, 100, false)
+        },
+    ];
+    let options = setup_options(2000);
+    assert_eq!(
+        select_coin_fifo(&inputs, options).unwrap().chosen_algorithm,
+        Algorithm::Fifo
+    );
+
+    // Every candidate wastes the drain cost; the fewest inputs win, only drawn by SRD.
+    let selection = select_coin_coin_age(&inputs, options, &mut rand::thread_rng()).unwrap();
+    assert_eq!(selection.waste.0, options.drain_cost as i64);
+    assert_eq!(selection.selected_inputs, vec![0]);
+    assert_eq!(selection.chosen_algorithm, Algorithm::Srd);
+}
+
+#[test]
+fn test_changeless_solution_exists() {
+    let inputs = OutputGroup::from_utxos(&[
+        (6000, 20, false),
+        (4000, 20, false),
+        (7000, 20, false),
+        (3000, 20, false),
+    ]);
+    let mut cases: Vec<CoinSelectionOpt> = [9000, 9950, 9960, 12950, 3000, 2000, 30000]
+        .map(setup_options)
+        .into();
+    // Invalid options are rejected before searching.
+    cases.push(setup_options(0));
+    for (case, options) in cases.iter().enumerate() {
+        // Some subset of the inputs lands in the match window.
+        let window = MatchParameters::from_options(options).unwrap();
+        let in_window = options.validate().is_ok()
+            && (1..1 << inputs.len()).any(|subset: usize| {
+                let effective_value: i128 = (0..inputs.len())
+                    .filter(|&index| subset & (1 << index) != 0)
+                    .map(|index| effective_value(&inputs[index], options))
+                    .sum();
+                (i128::from(window.target_for_match())
+                    ..=i128::from(window.target_for_match() + window.match_range()))
+                    .contains(&effective_value)
+            });
+        for seed in 0..8 {
+            let exists =
+                changeless_solution_exists(&inputs, *options, &mut StdRng::seed_from_u64(seed));
+            let found = options.validate().is_ok()
+                && bnb_changeless(&inputs, *options, &[], &mut StdRng::seed_from_u64(seed)).is_ok();
+            assert_eq!(
+                exists, found,
+                "disagreement in case {case} with seed {seed}"
+            );
+            assert_eq!(exists, in_window, "case {case} with seed {seed}");
+        }
+    }
+
+    // Only the match window is checked: both matches at 9960 leave 15 sats of excess, which the
+    // selection rejects above a cap of 10.
+    let mut options = setup_options(9960);
+    options.max_excess_to_fee = Some(10);
+    let mut rng = StdRng::seed_from_u64(225);
+    assert!(changeless_solution_exists(&inputs, options, &mut rng));
+    assert!(bnb_changeless(&inputs, options, &[], &mut rng).is_err());
+    assert!(!changeless_solution_exists(
+        &inputs,
+        setup_options(0),
+        &mut rng
+    ));
+}
+
+#[test]
+fn test_changeless_only_inputs() {
+    let mut inputs = OutputGroup::from_utxos(&[(6000, 20, false), (4000, 20, false)]);
+    inputs[1].changeless_only = true;
+    let mut rng = rand::thread_rng();
+
+    // An exact match may spend the changeless-only input.
+    let options = setup_options(9950);
+    let result = select_coin_bnb(&inputs, options, &mut rng).unwrap();
+    assert_eq!(result.chosen_algorithm, Algorithm::Bnb);
+    assert!(result.selected_inputs.contains(&InputIndex(1)));
+
+    // Without a match, the SRD fallback creates change and must leave it alone.
+    let options = setup_options(5000);
+    let result = select_coin_bnb(&inputs, options, &mut rng).unwrap();
+    assert_eq!(result.chosen_algorithm, Algorithm::Srd);
+    assert_eq!(result.selected_inputs, vec![0]);
+    assert!(matches!(
+        select_coin_fifo(&inputs, setup_options(7000)),
+        Err(SelectionError::InsufficientFunds)
+    ));
+
+    // Once the excess goes to fees there is no change, so the input is usable again.
+    let mut options = setup_options(7000);
+    options.excess_strategy = ExcessStrategy::ToFee;
+    let result = select_coin_fifo(&inputs, options).unwrap();
+    assert!(result.selected_inputs.contains(&InputIndex(1)));
+}
+
+#[test]
+fn test_selection_output_formatting() {
+    let output = SelectionOutput {
+        selected_inputs: vec![InputIndex(4), InputIndex(0), InputIndex(7)],
+        waste: WasteMetric(123),
+        audit: None,
+        chosen_algorithm: Algorithm::Bnb,
+        change_value: None,
+        iterations: 0,
+    };
+    let display = output.to_string();
+    assert!(display.contains("waste 123,"));
+    assert!(display.contains("3 inputs"));
+    assert!(display.contains("changeless"));
+
+    let debug = format!("{output:?}");
+    assert!(debug.contains("waste: 123,"));
+    assert!(debug.contains("input_count: 3"));
+    assert!(debug.contains("selected_inputs: [4, 0, 7]"));
+}
+
+#[test]
+fn test_select_coin_from_pools() {
+    let mut inputs = OutputGroup::from_utxos(&[
+        (50000, 20, false),
+        (3000, 20, false),
+        (4000, 20, false),
+        (80000, 20, false),
+    ]);
+    inputs[0].pool = Pool::Reserve;
+    inputs[3].pool = Pool::Reserve;
+
+    // The spending pool covers the target, so the reserves stay untouched.
+    let result = select_coin_from_pools(&inputs, setup_options(5000), select_coin_fifo).unwrap();
+    let mut selected = result.selected_inputs.clone();
+    selected.sort();
+    assert_eq!(selected, vec![1, 2]);
+
+    // Beyond the spending pool, the reserves are drawn on.
+    let result = select_coin_from_pools(&inputs, setup_options(20000), select_coin_fifo).unwrap();
+    assert!(result
+        .selected_inputs
+        .iter()
+        .any(|&index| inputs[index].pool == Pool::Reserve));
+}
+
+#[test]
+fn test_select_coin_recent_change() {
+    let mut inputs = OutputGroup::from_utxos(&[
+        (5000, 20, false),
+        (3000, 20, false),
+        (4000, 20, false),
+        (9000, 20, false),
+    ]);
+    inputs[0].creation_sequence = Some(7);
+    inputs[1].creation_sequence = Some(2);
+    inputs[2].creation_sequence = Some(9);
+    let options = setup_options(7000);
+
+    let result = select_coin_recent_change(&inputs, options).unwrap();
+    assert_eq!(result.selected_inputs, vec![2, 0]);
+    assert_eq!(result.chosen_algorithm, Algorithm::RecentChange);
+    let (value, weight) = selection_totals(&inputs, &result.selected_inputs, &options).unwrap();
+    assert!(covers_target(&options, value, weight));
+
+    // The input without a sequence is spent last.
+    let result = select_coin_recent_change(&inputs, setup_options(12000)).unwrap();
+    assert_eq!(result.selected_inputs, vec![2, 0, 1, 3]);
+}
+
+#[test]
+fn test_effective_value_checked() {
+    let normal = OutputGroup::single(1000, 100, false);
+    assert_eq!(
+        effective_value_checked(&normal, FeeRate::from_sat_per_kwu(2000)),
+        Ok(800)
+    );
+
+    let dust = OutputGroup::single(200, 100, false);
+    assert_eq!(
+        effective_value_checked(&dust, FeeRate::from_sat_per_kwu(2000)),
+        Err(DustError {
+            value: 200,
+            fee: 200
+        })
+    );
+    assert_eq!(
+        effective_value_checked(&dust, FeeRate::from_sat_per_kwu(3000)),
+        Err(DustError {
+            value: 200,
+            fee: 300
+        })
+    );
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_select_coin_bnb_parallel() {
+    let inputs = setup_basic_output_groups();
+    let options = setup_options(4730);
+    let result = select_coin_bnb_parallel(&inputs, options, 4, SeedSource::Os).unwrap();
+    assert_eq!(result.chosen_algorithm, Algorithm::Bnb);
+    // 1900 + 2850 is the only combination of effective values in the match window.
+    let mut selected = result.selected_inputs.clone();
+    selected.sort();
+    assert_eq!(selected, vec![1, 2]);
+
+    let parallel = select_coin_bnb_parallel(&inputs, options, 1, SeedSource::Fixed(3)).unwrap();
+    let sequential =
+        select_coin_bnb_changeless(&inputs, options, &mut SeedSource::Fixed(3).rng()).unwrap();
+    assert_eq!(parallel.selected_inputs, sequential.selected_inputs);
+    assert_eq!(parallel.waste.0, sequential.waste.0);
+
+    // No threads still runs the one search.
+    let none = select_coin_bnb_parallel(&inputs, options, 0, SeedSource::Fixed(3)).unwrap();
+    assert_eq!(none, parallel);
+}
+
+#[test]
+fn test_drain_counted_once_in_waste() {
+    let inputs = OutputGroup::from_utxos(&[(6000, 20, false), (4000, 20, false)]);
+    let mut options = setup_options(5000);
+    let result = select_coin_fifo(&inputs, options).unwrap();
+    assert_eq!(result.waste.0, options.drain_cost as i64);
+
+    // The drain's creation cost is part of `drain_cost`, so its weight does not add to it.
+    options.drain_weight = 500;
+    let result = select_coin_fifo(&inputs, options).unwrap();
+    assert_eq!(result.waste.0, options.drain_cost as i64);
+
+    // The timing cost of a single input with the base weight: fee 15 - 1 * 0.25 * 30 = 7.
+    options.long_term_feerate = Some(FeeRate::from_sat_per_kwu(250));
+    let result = select_coin_fifo(&inputs, options).unwrap();
+    assert_eq!(result.selected_inputs, vec![0]);
+    assert_eq!(result.waste.0, 7 + options.drain_cost as i64);
+}
+
+#[test]
+fn test_min_efficiency() {
+    // Effective values at 500 sat/kWU: 5990 (99.8%), 3000 (50%) and 3950 (98.75%).
+    let inputs =
+        OutputGroup::from_utxos(&[(6000, 20, false), (6000, 6000, false), (4000, 100, false)]);
+    let mut options = setup_options(8000);
+    options.min_efficiency = Some(0.9);
+
+    let mut rng = rand::thread_rng();
+    let results = [
+        select_coin_fifo(&inputs, options),
+        select_coin_lowestlarger(&inputs, options),
+        select_coin_srd(&inputs, options, &mut rng),
+        select_coin_recent_change(&inputs, options),
+    ];
+    for result in results {
+        let mut selected = result.unwrap().selected_inputs;
+        selected.sort();
+        assert_eq!(selected, vec![0, 2]);
+    }
+    // BnB does not consider the inefficient input either.
+    assert!(bnb_candidates(&inputs, &options)
+        .iter()
+        .all(|&(index, _)| index != 1));
+
+    options.min_efficiency = Some(0.5);
+    assert_eq!(bnb_candidates(&inputs, &options).len(), 3);
+}
+
+/// Compare `actual` with the committed snapshot `tests/snapshots/<name>.snap`.
+/// Run with `UPDATE_SNAPSHOTS=1` to write the snapshots instead, once a change is intended.
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{name}.snap"));
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("missing snapshot {}", path.display()));
+    assert_eq!(
+        actual, expected,
+        "snapshot {name} drifted, rerun with UPDATE_SNAPSHOTS=1 if intended"
+    );
+}
+
+#[test]
+fn test_selection_snapshots() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let mut inputs = OutputGroup::from_utxos(&[
+        (55000, 500, false),
+        (40000, 200, true),
+        (40000, 300, false),
+        (25000, 100, true),
+        (35000, 150, false),
+        (60000, 250, true),
+        (30000, 120, false),
+        (5000, 50, true),
+    ]);
+    for (sequence, input) in inputs.iter_mut().enumerate() {
+        input.creation_sequence = Some(sequence as u32);
+    }
+    let mut options = setup_options(100000);
+    options.long_term_feerate = Some(FeeRate::from_sat_per_kwu(100));
+
+    // The BnB target puts 59875 + 39900 = 99775 of effective value in the match window.
+    let bnb_options = CoinSelectionOpt {
+        target_value: 99750,
+        ..options
+    };
+    let snapshots = [
+        (
+            "bnb",
+            select_coin_bnb_changeless(&inputs, bnb_options, &mut StdRng::seed_from_u64(7)),
+        ),
+        (
+            "srd",
+            srd(&inputs, options, &[], &mut StdRng::seed_from_u64(7)),
+        ),
+        ("fifo", select_coin_fifo(&inputs, options)),
+        ("lowestlarger", select_coin_lowestlarger(&inputs, options)),
+        ("recent_change", select_coin_recent_change(&inputs, options)),
+    ];
+    for (name, result) in snapshots {
+        assert_snapshot(name, &format!("{result:#?}\n"));
+    }
+}
+
+#[test]
+fn test_select_coin_optimal() {
+    let inputs = OutputGroup::from_utxos(&[
+        (55000, 500, false),
+        (40000, 200, true),
+        (40000, 300, false),
+        (25000, 100, true),
+        (35000, 150, false),
+        (60000, 250, true),
+        (30000, 120, false),
+        (5000, 50, true),
+        (12000, 80, false),
+        (18000, 90, true),
+    ]);
+    let mut options = setup_options(100000);
+    options.long_term_feerate = Some(FeeRate::from_sat_per_kwu(100));
+
+    let optimal = select_coin_optimal(&inputs, options).unwrap();
+    assert_eq!(optimal.chosen_algorithm, Algorithm::Optimal);
+    let (value, weight) = selection_totals(&inputs, &optimal.selected_inputs, &options).unwrap();
+    assert!(covers_target(&options, value, weight));
+
+    // A heuristic is never better than the optimum. No subset lands in the match window, so every
+    // selection has change and a heuristic can only lose the timing cost of its inputs.
+    assert!(optimal.change_value.is_some());
+    let mut rng = rand::thread_rng();
+    let heuristics = [
+        select_coin_bnb(&inputs, options, &mut rng).unwrap(),
+        select_coin_lowestlarger(&inputs, options).unwrap(),
+        select_coin_fifo(&inputs, options).unwrap(),
+        select_coin_srd(&inputs, options, &mut rng).unwrap(),
+    ];
+    for heuristic in heuristics {
+        let (_, weight) = selection_totals(&inputs, &heuristic.selected_inputs, &options).unwrap();
+        assert!(heuristic.waste.0 >= optimal.waste.0, "{heuristic}");
+        assert!(
+            heuristic.waste.0
+                <= optimal.waste.0 + calculate_fee(weight, options.target_feerate) as i64,
+            "{heuristic}"
+        );
+    }
+    assert!(matches!(
+        select_coin_optimal(&vec![inputs[0]; 25], options),
+        Err(SelectionError::TooManyInputs)
+    ));
+}
+
+#[test]
+fn test_select_coin_optimal_changeless() {
+    let inputs =
+        OutputGroup::from_utxos(&[(3000, 20, false), (7000, 20, false), (2000, 20, false)]);
+    let mut options = setup_options(9950);
+    options.drain_cost = 50;
+
+    // The first two inputs land in the match window and pay their excess of 25 as fee, which
+    // wastes less than the drain output of spending all three.
+    let optimal = select_coin_optimal(&inputs, options).unwrap();
+    assert_eq!(optimal.selected_inputs, vec![0, 1]);
+    assert_eq!(optimal.change_value, None);
+    assert_eq!(optimal.waste.0, 25);
+    assert!(compute_waste(&inputs, &[0usize, 1, 2], &options) > optimal.waste.0);
+
+    let bnb = select_coin_bnb(&inputs, options, &mut StdRng::seed_from_u64(236)).unwrap();
+    assert_eq!(bnb.chosen_algorithm, Algorithm::Bnb);
+    assert_eq!(bnb.waste, optimal.waste);
+
+    // With a cheap drain, the change output wastes less.
+    options.drain_cost = 10;
+    let optimal = select_coin_optimal(&inputs, options).unwrap();
+    assert_eq!(optimal.selected_inputs, vec![0, 1, 2]);
+    assert!(optimal.change_value.is_some());
+}
+
+#[test]
+fn test_select_coin_min_risk() {
+    let inputs =
+        OutputGroup::from_utxos(&[(20000, 20, false), (6000, 20, false), (6000, 20, false)]);
+    let scores = [0.9, 0.1, 0.2];
+    let mut options = setup_options(10000);
+    options.long_term_feerate = Some(FeeRate::from_sat_per_kwu(100));
+
+    // Spending the single large input wastes the least, but it carries the most risk.
+    let least_waste = select_coin_optimal(&inputs, options).unwrap();
+    assert_eq!(least_waste.selected_inputs, vec![0]);
+
+    let result = select_coin_min_risk(&inputs, options, &scores).unwrap();
+    assert_eq!(result.selected_inputs, vec![1, 2]);
+    assert_eq!(result.chosen_algorithm, Algorithm::MinRisk);
+    assert!(result.waste.0 > least_waste.waste.0);
+
+    // Every input needs a score.
+    assert!(matches!(
+        select_coin_min_risk(&inputs, options, &scores[..2]),
+        Err(SelectionError::MissingRiskScores)
+    ));
+}
+
+#[test]
+fn test_sub_dust_policy() {
+    // The first input alone leaves 6000 - 5690 - 40 = 270 of change, below 500.
+    let inputs = OutputGroup::from_utxos(&[(6000, 20, false), (4000, 20, false)]);
+    let mut options = setup_options(5690);
+
+    options.sub_dust_policy = SubDustPolicy::ForceMoreInputs;
+    let result = select_coin_fifo(&inputs, options).unwrap();
+    assert_eq!(result.selected_inputs, vec![0, 1]);
+    assert_eq!(result.waste.0, options.drain_cost as i64);
+
+    options.sub_dust_policy = SubDustPolicy::ToFee;
+    let result = select_coin_fifo(&inputs, options).unwrap();
+    assert_eq!(result.selected_inputs, vec![0]);
+    // Without the drain output, the fee is 15 and the excess 295.
+    assert_eq!(result.waste.0, 295);
+
+    options.sub_dust_policy = SubDustPolicy::Reject;
+    assert!(matches!(
+        select_coin_fifo(&inputs, options),
+        Err(SelectionError::WouldCreateDust)
+    ));
+}
+
+#[test]
+fn test_partition_selection() {
+    let inputs = generate_portfolio(&mut rand::thread_rng(), 300);
+    let selected: Vec<InputIndex> = (0..inputs.len()).map(InputIndex).collect();
+    let (max_tx_weight, base_weight) = (20000, 400);
+
+    let batches = partition_selection(&selected, &inputs, max_tx_weight, base_weight);
+    assert!(batches.len() > 1);
+    for batch in &batches {
+        let weight: u32 = batch.iter().map(|&index| inputs[index].weight).sum();
+        assert!(base_weight + weight <= max_tx_weight);
+    }
+    let mut partitioned: Vec<InputIndex> = batches.into_iter().flatten().collect();
+    partitioned.sort();
+    assert_eq!(partitioned, selected);
+}
+
+#[test]
+fn test_max_change_ratio() {
+    let inputs = OutputGroup::from_utxos(&[(20000, 20, false), (6000, 20, false)]);
+    let mut options = setup_options(5000);
+    options.max_change_ratio = Some(0.2);
+
+    // FIFO spends the large input, leaving 14990 of change for a 5000 payment.
+    assert!(matches!(
+        select_coin_fifo(&inputs, options),
+        Err(SelectionError::NoSolutionFound)
+    ));
+    // Lowest Larger leaves 990, within the allowed 1000.
+    let result = select_coin_lowestlarger(&inputs, options).unwrap();
+    assert_eq!(result.selected_inputs, vec![1]);
+}
+
+#[test]
+fn test_output_groups_from() {
+    struct UtxoRow {
+        outpoint: &'static str,
+        amount_sat: u64,
+        confirmations: u32,
+    }
+    let rows = [
+        UtxoRow {
+            outpoint: "aa:0",
+            amount_sat: 3000,
+            confirmations: 12,
+        },
+        UtxoRow {
+            outpoint: "bb:1",
+            amount_sat: 9000,
+            confirmations: 1,
+        },
+        UtxoRow {
+            outpoint: "cc:0",
+            amount_sat: 5000,
+            confirmations: 40,
+        },
+    ];
+    let inputs = output_groups_from(&rows, |row| OutputGroup {
+        creation_sequence: Some(u32::MAX - row.confirmations),
+        ..OutputGroup::single(row.amount_sat, 272, true)
+    });
+    assert_eq!(inputs.len(), rows.len());
+
+    // FIFO spends the most confirmed row first.
+    let result = select_coin_fifo(&inputs, setup_options(4000)).unwrap();
+    let spent: Vec<&str> = result
+        .selected_inputs
+        .iter()
+        .map(|&index| rows[index.0].outpoint)
+        .collect();
+    assert_eq!(spent, vec!["cc:0"]);
+}
+
+#[test]
+fn test_fee_buffer_percent() {
+    // Each input pays 500 in fees at 500 sat/kWU.
+    let inputs = OutputGroup::from_utxos(&[(5000, 1000, false); 4]);
+    let mut options = setup_options(8450);
+
+    let nominal = select_coin_fifo(&inputs, options).unwrap();
+    assert_eq!(nominal.selected_inputs.len(), 2);
+
+    options.fee_buffer_percent = 10.0;
+    let buffered = select_coin_fifo(&inputs, options).unwrap();
+    assert_eq!(buffered.selected_inputs.len(), 3);
+    let (value, weight) = selection_totals(&inputs, &buffered.selected_inputs, &options).unwrap();
+    let fee_above_estimate = calculate_fee(weight, options.target_feerate) * 11 / 10;
+    assert!(value >= options.target_value + options.min_drain_value + fee_above_estimate);
+
+    // The base weight's fee of 5 becomes 5.5, rounded up.
+    let match_parameters = MatchParameters::from_options(&options).unwrap();
+    assert_eq!(match_parameters.target_for_match(), 8450 + 6 + 10);
+}
+
+#[test]
+fn test_selection_output_ord() {
+    let output = |selected_inputs: Vec<usize>, waste, chosen_algorithm| SelectionOutput {
+        change_value: None,
+        iterations: 0,
+        selected_inputs: selected_inputs.into_iter().map(InputIndex).collect(),
+        waste: WasteMetric(waste),
+        audit: None,
+        chosen_algorithm,
+    };
+    let outputs = [
+        output(vec![0, 1], 40, Algorithm::Fifo),
+        output(vec![2], 15, Algorithm::Srd),
+        output(vec![3, 0], 15, Algorithm::Srd),
+        output(vec![1], 15, Algorithm::LowestLarger),
+        output(vec![4], 90, Algorithm::Bnb),
+    ];
+    // The least waste wins, then fewer inputs, then the lowest indices.
+    let best = outputs.iter().min().unwrap();
+    assert_eq!(best.selected_inputs, vec![1]);
+    assert_eq!(best.chosen_algorithm, Algorithm::LowestLarger);
+    assert_eq!(outputs.iter().max().unwrap().waste, WasteMetric(90));
+    assert!(outputs[1] < outputs[2]);
+    let by_waste = outputs.iter().min_by(|a, b| a.cmp_by_waste(b)).unwrap();
+    assert_eq!(by_waste, best);
+}
+
+#[test]
+fn test_bnb_unwinds_selected_inputs() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    // Effective values 950, 1900 and 2850: no subset lands in a window starting at 3000.
+    let inputs = setup_basic_output_groups();
+    let options = setup_options(2975);
+    let match_parameters = MatchParameters::from_options(&options).unwrap();
+    let candidates = bnb_candidates(&inputs, &options);
+    for seed in 0..64 {
+        for forbidden_pairs in [&[][..], &[(InputIndex(1), InputIndex(2))][..]] {
+            let mut selected_inputs = Vec::new();
+            let mut bnb_tries = BNB_TRIES;
+            let result = bnb(
+                &candidates,
+                &mut selected_inputs,
+                0,
+                0,
+                &mut bnb_tries,
+                &match_parameters,
+                forbidden_pairs,
+                &mut StdRng::seed_from_u64(seed),
+                |selected_inputs| selected_inputs.to_vec(),
+            );
+            assert!(matches!(result, Err(SelectionError::NoSolutionFound)));
+            assert!(
+                selected_inputs.is_empty(),
+                "seed {seed} left {selected_inputs:?} behind"
+            );
+        }
+    }
+    assert!(matches!(
+        select_coin_bnb_changeless(&inputs, options, &mut StdRng::seed_from_u64(0)),
+        Err(SelectionError::NoSolutionFound)
+    ));
+}
+
+#[test]
+fn test_select_coin_in_millisats() {
+    #[derive(Clone, Copy)]
+    struct MilliSats(u64);
+
+    impl Amount for MilliSats {
+        const UNITS_PER_SAT: u64 = 1000;
+
+        fn to_units(self) -> u64 {
+            self.0
+        }
+
+        fn from_units(units: u64) -> Self {
+            MilliSats(units)
+        }
+    }
+
+    // At 4 sat/kWU each input pays 0.4 sat in fees.
+    let mut options = setup_options(0);
+    options.target_feerate = FeeRate::from_sat_per_kwu(4);
+    options.min_drain_value = 0;
+    options.drain_cost = 0;
+    let sats = [(1000u64, 100, false); 4];
+    let millisats =
+        sats.map(|(value, weight, is_segwit)| (MilliSats(value * 1000), weight, is_segwit));
+
+    // A 2998.5 sat target rounds up to 2999 in sats, which three inputs paying a 2 sat fee
+    // do not cover. In millisats they pay 1.2 sat and cover it.
+    let result = select_coin_in(&sats, 2999, options, select_coin_fifo).unwrap();
+    assert_eq!(result.selected_inputs.len(), 4);
+    let result = select_coin_in(&millisats, MilliSats(2998500), options, select_coin_fifo).unwrap();
+    assert_eq!(result.selected_inputs.len(), 3);
+    assert_eq!(MilliSats::from_units(1500).0, 1500);
+
+    // The dust threshold is in sats too, so 1000 sat inputs are dust in either unit.
+    options.dust_threshold = 1000;
+    for result in [
+        select_coin_in(&sats, 2999, options, select_coin_fifo),
+        select_coin_in(&millisats, MilliSats(2998500), options, select_coin_fifo),
+    ] {
+        assert!(matches!(result, Err(SelectionError::InsufficientFunds)));
+    }
+}
+
+#[test]
+fn test_select_coin_with_confirmed_input() {
+    let mut inputs = OutputGroup::from_utxos(&[
+        (9000, 20, false),
+        (8000, 20, false),
+        (3000, 20, false),
+        (4000, 20, false),
+    ]);
+    inputs[2].creation_sequence = Some(1);
+    inputs[3].creation_sequence = Some(2);
+    let options = setup_options(7000);
+
+    // The exhaustive search alone spends a single unconfirmed input.
+    let plain = select_coin_optimal(&inputs, options).unwrap();
+    assert_eq!(plain.selected_inputs, vec![0]);
+
+    for select in [
+        select_coin_fifo,
+        select_coin_lowestlarger,
+        select_coin_optimal,
+    ] {
+        let result = select_coin_with_confirmed_input(&inputs, options, select).unwrap();
+        assert!(result
+            .selected_inputs
+            .iter()
+            .any(|&index| inputs[index].creation_sequence.is_some()));
+        let (value, weight) = selection_totals(&inputs, &result.selected_inputs, &options).unwrap();
+        assert!(covers_target(&options, value, weight));
+    }
+
+    let unconfirmed = [inputs[0], inputs[1]];
+    assert!(matches!(
+        select_coin_with_confirmed_input(&unconfirmed, options, select_coin_fifo),
+        Err(SelectionError::NoConfirmedInput)
+    ));
+}
+
+#[test]
+fn test_estimate_change() {
+    let inputs = generate_portfolio(&mut rand::thread_rng(), 20);
+    let largest = inputs.iter().map(|input| input.value).max().unwrap();
+    let change = |selection: &SelectionOutput, options: &CoinSelectionOpt| {
+        let (value, weight) =
+            selection_totals(&inputs, &selection.selected_inputs, options).unwrap();
+        let weight = weight + options.base_weight + options.drain_weight;
+        value - options.target_value - calculate_fee(weight, options.target_feerate)
+    };
+
+    let total: u64 = inputs.iter().map(|input| input.value).sum();
+    for target in [total / 10, total / 4, total / 2] {
+        let options = setup_options(target);
+        let estimate = estimate_change(&inputs, &options).unwrap();
+
+        let lowestlarger = select_coin_lowestlarger(&inputs, options).unwrap();
+        assert_eq!(estimate, change(&lowestlarger, &options));
+        assert_eq!(lowestlarger.change_value, Some(estimate));
+        let fifo = select_coin_fifo(&inputs, options).unwrap();
+        assert!(estimate.abs_diff(change(&fifo, &options)) <= largest);
+    }
+    assert_eq!(estimate_change(&inputs, &setup_options(total)), None);
+}
+
+#[test]
+fn test_seed_source_reproduces_selections() {
+    let inputs = generate_portfolio(&mut rand::thread_rng(), 12);
+    let total: u64 = inputs.iter().map(|input| input.value).sum();
+    let options = setup_options(total / 3);
+
+    let bytes = b"same".to_vec();
+    for seed in [SeedSource::Fixed(42), SeedSource::Derived(b"same")] {
+        let replay = match seed {
+            SeedSource::Derived(_) => SeedSource::Derived(&bytes),
+            seed => seed,
+        };
+        let first = srd(&inputs, options, &[], &mut seed.rng()).unwrap();
+        let second = srd(&inputs, options, &[], &mut replay.rng()).unwrap();
+        assert_eq!(first, second);
+
+        let first = select_coin_bnb_changeless(&inputs, options, &mut seed.rng());
+        let second = select_coin_bnb_changeless(&inputs, options, &mut replay.rng());
+        assert_eq!(first.ok(), second.ok());
+    }
+    assert_ne!(
+        SeedSource::Derived(b"same").rng().gen::<u64>(),
+        SeedSource::Derived(b"other").rng().gen::<u64>()
+    );
+}
+
+#[test]
+fn test_prefer_clusters() {
+    let mut inputs = OutputGroup::from_utxos(&[(6000, 20, false); 3]);
+    inputs[2].input_count = 3;
+    let mut options = setup_options(5000);
+    options.cost_per_input = 0;
+
+    // Without a cost per input, every input covers the target alone with the same waste.
+    let result = select_coin_optimal(&inputs, options).unwrap();
+    assert_eq!(result.selected_inputs, vec![0]);
+
+    options.prefer_clusters = true;
+    let result = select_coin_optimal(&inputs, options).unwrap();
+    assert_eq!(result.selected_inputs, vec![2]);
+}
+
+#[test]
+fn test_input_index_refers_to_original_slice() {
+    let mut inputs = OutputGroup::from_utxos(&[
+        (90000, 20, false),
+        (70000, 20, false),
+        (60000, 20, false),
+        (9000, 20, false),
+    ]);
+    for input in &mut inputs[..3] {
+        input.pool = Pool::Reserve;
+    }
+
+    // The selector only sees the last input, as its index 0, yet the result indexes `inputs`.
+    let result =
+        select_coin_from_pools(&inputs, setup_options(5000), select_coin_lowestlarger).unwrap();
+    assert_eq!(result.selected_inputs, vec![InputIndex(3)]);
+    assert_eq!(inputs[result.selected_inputs[0]].value, 9000);
+}
+
+#[test]
+fn test_select_coin_anchor_topup() {
+    let inputs = OutputGroup::from_utxos(&[
+        (30000, 20, false),
+        (9000, 20, false),
+        (4000, 20, false),
+        (2500, 20, false),
+        (1200, 20, false),
+    ]);
+    let options = setup_options(12000);
+    let change = |selected_inputs: &[InputIndex]| {
+        let (value, weight) = selection_totals(&inputs, selected_inputs, &options).unwrap();
+        value - options.target_value - calculate_fee(weight, options.target_feerate)
+    };
+
+    let result = select_coin_anchor_topup(&inputs, options).unwrap();
+    assert_eq!(result.selected_inputs, vec![1, 2]);
+    assert_eq!(result.chosen_algorithm, Algorithm::AnchorTopup);
+
+    let largest_first = select_coin_largest_first(&inputs, options).unwrap();
+    assert!(change(&result.selected_inputs) <= change(&largest_first.selected_inputs));
+
+    assert!(matches!(
+        select_coin_anchor_topup(&inputs, setup_options(50000)),
+        Err(SelectionError::InsufficientFunds)
+    ));
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_waste_model_reference() {
+    let mut options = setup_options(2500);
+    options.long_term_feerate = Some(FeeRate::from_sat_per_kwu(200));
+    // Two inputs weighing 500 with the base weight and 5000 of value pay 250 in fee; 100 at the
+    // long-term feerate.
+    let waste = |options: &CoinSelectionOpt| {
+        let creates_drain = options.excess_strategy == ExcessStrategy::ToDrain;
+        calculate_waste(&[1, 2], options, 5000, 490, creates_drain).unwrap()
+    };
+    let cases = [
+        (ExcessStrategy::ToDrain, 200, 150 + 10, 50 + 10),
+        (ExcessStrategy::ToFee, 200, 150 + 2250, 50 + 2250),
+        // The recipient receives the excess, so it is not wasted.
+        (ExcessStrategy::ToRecipient, 200, 150, 50),
+        // Spending now saves 250 over the long-term feerate, more than the drain costs.
+        (ExcessStrategy::ToDrain, 1000, 250 - 500 + 10, 10),
+    ];
+    for (excess_strategy, long_term_feerate, consolidated, legacy) in cases {
+        options.excess_strategy = excess_strategy;
+        options.long_term_feerate = Some(FeeRate::from_sat_per_kwu(long_term_feerate));
+        options.waste_model = WasteModel::Consolidated;
+        assert_eq!(waste(&options), consolidated, "{excess_strategy:?}");
+        options.waste_model = WasteModel::Legacy;
+        assert_eq!(waste(&options), legacy, "{excess_strategy:?}");
+    }
+}
+
+/// Over generated input sets with a planted changeless solution, count how often the randomized
+/// `select_coin_bnb` finds a match within `BNB_TRIES` instead of falling back to SRD, and how
+/// often `select_coin_bnb_deterministic` does.
+/// Run with `cargo test -- --ignored --nocapture` to see the rate.
+#[test]
+#[ignore = "statistical, prints the success rate"]
+fn test_bnb_success_rate() {
+    const SETS: usize = 100;
+    let mut rng = rand::thread_rng();
+    let (mut planted_sets, mut found, mut found_deterministic) = (0, 0, 0);
+    for _ in 0..SETS {
+        let inputs = generate_portfolio(&mut rng, 20);
+        let mut options = setup_options(0);
+        let window = MatchParameters::from_options(&options).unwrap();
+
+        // A random subset whose effective value lands in the match window proves a match exists.
+        let planted: u64 = inputs
+            .iter()
+            .filter(|_| rng.gen_bool(0.3))
+            .filter_map(|input| effective_value_checked(input, options.target_feerate).ok())
+            .sum();
+        let overshoot = rng.gen_range(0..=window.match_range());
+        let Some(target) = planted.checked_sub(window.target_for_match() + overshoot) else {
+            continue;
+        };
+        options.target_value = target;
+        planted_sets += 1;
+
+        let selection = select_coin_bnb(&inputs, options, &mut rng);
+        if selection.is_ok_and(|selection| selection.chosen_algorithm == Algorithm::Bnb) {
+            found += 1;
+        }
+        if select_coin_bnb_deterministic(&inputs, options).is_ok() {
+            found_deterministic += 1;
+        }
+    }
+    println!("randomized select_coin_bnb found a match in {found} of {planted_sets} sets");
+    println!("select_coin_bnb_deterministic found a match in {found_deterministic} of {planted_sets} sets");
+    assert!(planted_sets > 0);
+}
+
+#[test]
+fn test_bnb_tries_budget() {
+    // Effective values 950, 1900 and 2850: no subset lands in a window starting at 3000.
+    let inputs = setup_basic_output_groups();
+    let options = setup_options(2975);
+    let match_parameters = MatchParameters::from_options(&options).unwrap();
+    let candidates = bnb_candidates(&inputs, &options);
+    let search = |bnb_tries: &mut u32, seed: u64| {
+        bnb(
+            &candidates,
+            &mut Vec::new(),
+            0,
+            0,
+            bnb_tries,
+            &match_parameters,
+            &[],
+            &mut StdRng::seed_from_u64(seed),
+            |selected_inputs| selected_inputs.to_vec(),
+        )
+    };
+
+    // An exhaustive search spends the same number of tries whichever branches it takes first.
+    let mut bnb_tries = BNB_TRIES;
+    assert!(matches!(
+        search(&mut bnb_tries, 0),
+        Err(SelectionError::NoSolutionFound)
+    ));
+    let spent = BNB_TRIES - bnb_tries;
+    for seed in 1..64 {
+        let mut bnb_tries = BNB_TRIES;
+        assert!(matches!(
+            search(&mut bnb_tries, seed),
+            Err(SelectionError::NoSolutionFound)
+        ));
+        assert_eq!(BNB_TRIES - bnb_tries, spent, "seed {seed}");
+    }
+    // Exactly the tries needed still explore the whole tree.
+    let mut bnb_tries = spent;
+    assert!(matches!(
+        search(&mut bnb_tries, 0),
+        Err(SelectionError::NoSolutionFound)
+    ));
+
+    // A smaller budget is spent in full and never exceeded.
+    let mut bnb_tries = spent - 1;
+    assert!(matches!(
+        search(&mut bnb_tries, 0),
+        Err(SelectionError::SearchBudgetExhausted)
+    ));
+    assert_eq!(bnb_tries, 0);
+}
+
+#[test]
+fn test_bnb_waste_includes_fee() {
+    // Both feerates put 3000 + 7000 in the match window, leaving a different excess as fee.
+    let inputs = OutputGroup::from_utxos(&[(3000, 20, false), (7000, 20, false)]);
+    let mut options = setup_options(9960);
+    options.excess_strategy = ExcessStrategy::ToFee;
+    let mut rng = StdRng::seed_from_u64(254);
+
+    let paid = select_coin_bnb_changeless(&inputs, options, &mut rng).unwrap();
+    options.target_feerate = FeeRate::from_sat_per_kwu(0);
+    let free = select_coin_bnb_changeless(&inputs, options, &mut rng).unwrap();
+    assert_eq!(paid.selected_inputs.len(), 2);
+    assert_eq!(free.selected_inputs.len(), 2);
+
+    // 10000 - 9960 - 0.5 * (40 + 10), against no fee at all.
+    assert_eq!(paid.waste, WasteMetric(15));
+    assert_eq!(free.waste, WasteMetric(40));
+}
+
+#[test]
+fn test_select_coin_srd_change_above_dust() {
+    let mut rng = StdRng::seed_from_u64(255);
+    let options = setup_options(60000);
+    for _ in 0..50 {
+        let inputs = generate_portfolio(&mut rng, 20);
+        let Ok(selection) = select_coin_srd(&inputs, options, &mut rng) else {
+            continue;
+        };
+        assert_eq!(selection.chosen_algorithm, Algorithm::Srd);
+        let (value, weight) =
+            selection_totals(&inputs, &selection.selected_inputs, &options).unwrap();
+        let fee = calculate_fee(
+            weight + options.base_weight + options.drain_weight,
+            options.target_feerate,
+        );
+        assert!(value - options.target_value - fee >= options.min_drain_value);
+    }
+
+    let inputs = OutputGroup::from_utxos(&[(30000, 20, false), (30000, 20, false)]);
+    assert!(matches!(
+        select_coin_srd(&inputs, options, &mut rng),
+        Err(SelectionError::InsufficientFunds)
+    ));
+}
+
+#[test]
+fn test_seeded_selection_is_reproducible() {
+    let inputs = generate_portfolio(&mut StdRng::seed_from_u64(256), 30);
+    let options = setup_options(80000);
+    let select = |seed: u64| {
+        let mut rng = StdRng::seed_from_u64(seed);
+        [
+            select_coin_bnb(&inputs, options, &mut rng),
+            select_coin_srd(&inputs, options, &mut rng),
+            select_coin_knapsack(&inputs, options, &mut rng),
+            select_coin_coin_age(&inputs, options, &mut rng),
+        ]
+        .map(|selection| selection.ok().map(|selection| selection.selected_inputs))
+    };
+    for seed in 0..10 {
+        assert_eq!(select(seed), select(seed));
+    }
+}
+
+#[test]
+fn test_coin_selection_opt_builder_defaults() {
+    let options = CoinSelectionOptBuilder::new(1000, FeeRate::from_sat_per_kwu(500)).build();
+    assert_eq!(options.target_value, 1000);
+    assert_eq!(options.min_absolute_fee, 0);
+    assert_eq!(options.long_term_feerate, None);
+    assert_eq!(options.excess_strategy, ExcessStrategy::ToDrain);
+    assert_eq!(options.sub_dust_policy, SubDustPolicy::ForceMoreInputs);
+    assert_eq!(options.waste_model, WasteModel::Consolidated);
+
+    let options = CoinSelectionOptBuilder::new(1000, FeeRate::from_sat_per_kwu(500))
+        .long_term_feerate(FeeRate::from_sat_per_kwu(100))
+        .excess_strategy(ExcessStrategy::ToFee)
+        .min_drain_value(500)
+        .build();
+    assert_eq!(
+        options.long_term_feerate,
+        Some(FeeRate::from_sat_per_kwu(100))
+    );
+    assert_eq!(options.excess_strategy, ExcessStrategy::ToFee);
+    assert_eq!(options.min_drain_value, 500);
+}
+
+#[test]
+fn test_select_coin_least_waste() {
+    let mut rng = StdRng::seed_from_u64(258);
+    let inputs = generate_portfolio(&mut rng, 30);
+    let options = setup_options(80000);
+    // Replaying the RNG reproduces the candidates select_coin chooses from.
+    let mut replay = rng.clone();
+    let selection = select_coin(&inputs, options, &mut rng).unwrap();
+    let least_waste = [
+        select_coin_bnb_changeless(&inputs, options, &mut replay),
+        select_coin_knapsack(&inputs, options, &mut replay),
+        select_coin_srd(&inputs, options, &mut replay),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|candidate| candidate.waste.0)
+    .min();
+    assert_eq!(Some(selection.waste.0), least_waste);
+
+    // A changeless match paying 25 of excess as fee beats change-producing selections, once
+    // the drain costs more.
+    let inputs =
+        OutputGroup::from_utxos(&[(3000, 20, false), (7000, 20, false), (2000, 20, false)]);
+    let mut options = setup_options(9950);
+    let selection = select_coin(&inputs, options, &mut rng).unwrap();
+    assert_ne!(selection.chosen_algorithm, Algorithm::Bnb);
+    options.drain_cost = 50;
+    let selection = select_coin(&inputs, options, &mut rng).unwrap();
+    assert_eq!(selection.chosen_algorithm, Algorithm::Bnb);
+    assert_eq!(selection.waste.0, 25);
+
+    assert!(matches!(
+        select_coin(&inputs, setup_options(13000), &mut rng),
+        Err(SelectionError::InsufficientFunds)
+    ));
+    // The inputs hold the target value but can't pay its fee.
+    assert!(matches!(
+        select_coin(&inputs, setup_options(11900), &mut rng),
+        Err(SelectionError::NoSolutionFound)
+    ));
+}
+
+#[test]
+fn test_negative_waste_when_consolidating() {
+    let inputs = setup_basic_output_groups();
+    let mut options = setup_options(2500);
+    // The long-term feerate is far above the current 500 sat/kWU.
+    options.long_term_feerate = Some(FeeRate::from_sat_per_kwu(5000));
+    let selection = select_coin_fifo(&inputs, options).unwrap();
+    assert!(selection.waste.0 < 0, "{selection}");
+    assert!(selection.waste < WasteMetric(0));
+
+    let decoded = SelectionOutput::decode(&selection.encode()).unwrap();
+    assert_eq!(decoded.waste, selection.waste);
+    for waste in [i64::MIN, -1, 0, 1, i64::MAX] {
+        let selection = SelectionOutput {
+            selected_inputs: vec![InputIndex(0)],
+            waste: WasteMetric(waste),
+            audit: None,
+            chosen_algorithm: Algorithm::Fifo,
+            change_value: None,
+            iterations: 0,
+        };
+        let decoded = SelectionOutput::decode(&selection.encode()).unwrap();
+        assert_eq!(decoded.waste.0, waste);
+    }
+}
+
+#[test]
+fn test_bnb_deep_search_constant_stack() {
+    // Matching needs 100000 of the inputs, far deeper than a recursive search could go.
+    let inputs = vec![OutputGroup::single(1000, 20, false); 300_000];
+    let options = setup_options(100_000 * 990 - 15);
+    let mut rng = StdRng::seed_from_u64(262);
+    assert!(changeless_solution_exists(&inputs, options, &mut rng));
+    let selection = select_coin_bnb(&inputs, options, &mut rng).unwrap();
+    assert_eq!(selection.chosen_algorithm, Algorithm::Bnb);
+    assert_eq!(selection.selected_inputs.len(), 100_000);
+}
+
+#[test]
+fn test_select_coin_bnb_deterministic() {
+    let inputs = generate_portfolio(&mut StdRng::seed_from_u64(263), 16);
+    let mut options = setup_options(0);
+    options.long_term_feerate = Some(FeeRate::from_sat_per_kwu(100));
+    let window = MatchParameters::from_options(&options).unwrap();
+    // Plant a match over the first half of the inputs.
+    let planted: u64 = inputs[..8]
+        .iter()
+        .filter_map(|input| effective_value_checked(input, options.target_feerate).ok())
+        .sum();
+    options.target_value = planted - window.target_for_match();
+
+    let selection = select_coin_bnb_deterministic(&inputs, options).unwrap();
+    assert_eq!(selection.chosen_algorithm, Algorithm::Bnb);
+    for _ in 0..5 {
+        let again = select_coin_bnb_deterministic(&inputs, options).unwrap();
+        assert_eq!(again.selected_inputs, selection.selected_inputs);
+    }
+    // No match the randomized search finds wastes less.
+    let mut rng = StdRng::seed_from_u64(263);
+    for _ in 0..20 {
+        if let Ok(randomized) = select_coin_bnb_changeless(&inputs, options, &mut rng) {
+            assert!(selection.waste <= randomized.waste);
+        }
+    }
+
+    assert!(matches!(
+        select_coin_bnb_deterministic(&inputs, setup_options(u64::MAX / 2)),
+        Err(SelectionError::NoSolutionFound)
+    ));
+}
+
+#[test]
+fn test_min_absolute_fee() {
+    // Four light inputs pay well below 1000 in fee at 500 sat/kWU.
+    let inputs = OutputGroup::from_utxos(&[
+        (3000, 20, false),
+        (7000, 20, false),
+        (2000, 20, false),
+        (1000, 20, false),
+    ]);
+    let mut options = setup_options(9950);
+    let selection = select_coin_bnb_deterministic(&inputs, options).unwrap();
+    let mut selected = selection.selected_inputs.clone();
+    selected.sort();
+    // Both 3000 + 7000 and 7000 + 2000 + 1000 match; the heavier one pays less excess as fee.
+    assert_eq!(selected, vec![1, 2, 3]);
+
+    // The match window moves up by the missing fee, so the 1000 input joins the match.
+    options.min_absolute_fee = 1000;
+    let selection = select_coin_bnb_deterministic(&inputs, options).unwrap();
+    let mut selected = selection.selected_inputs.clone();
+    selected.sort();
+    assert_eq!(selected, vec![0, 1, 3]);
+    let (value, _) = selection_totals(&inputs, &selection.selected_inputs, &options).unwrap();
+    assert!(value - options.target_value >= options.min_absolute_fee);
+
+    // Change-producing selections leave the minimum fee on top of the change.
+    options.target_value = 8000;
+    let selection = select_coin_fifo(&inputs, options).unwrap();
+    let (value, _) = selection_totals(&inputs, &selection.selected_inputs, &options).unwrap();
+    assert!(value >= options.target_value + options.min_absolute_fee + options.min_drain_value);
+}
+
+#[test]
+fn test_fifo_unconfirmed_last() {
+    let mut inputs = OutputGroup::from_utxos(&[
+        (5000, 20, false),
+        (1000, 20, false),
+        (2000, 20, false),
+        (3000, 20, false),
+    ]);
+    inputs[1].creation_sequence = Some(3);
+    inputs[2].creation_sequence = Some(1);
+
+    let result = select_coin_fifo(&inputs, setup_options(2000)).unwrap();
+    assert_eq!(result.selected_inputs, vec![2, 1]);
+    let result = select_coin_fifo(&inputs, setup_options(6000)).unwrap();
+    assert_eq!(result.selected_inputs, vec![2, 1, 0]);
+
+    // Spending all inputs pays 70 in fee with the base and drain weights, leaving 10430 once the
+    // 500 of change is set aside.
+    let result = select_coin_fifo(&inputs, setup_options(10430)).unwrap();
+    assert_eq!(result.selected_inputs, vec![2, 1, 0, 3]);
+    assert!(matches!(
+        select_coin_fifo(&inputs, setup_options(10440)),
+        Err(SelectionError::InsufficientFunds)
+    ));
+}
+
+#[test]
+fn test_select_coin_largest_first() {
+    let inputs = setup_bnb_sample_output_groups();
+    let result = select_coin_largest_first(&inputs, setup_options(50000)).unwrap();
+    assert_eq!(result.selected_inputs, vec![0]);
+    assert_eq!(result.chosen_algorithm, Algorithm::LargestFirst);
+
+    let options = setup_options(90000);
+    let result = select_coin_largest_first(&inputs, options).unwrap();
+    assert_eq!(result.selected_inputs, vec![0, 2]);
+    let (value, weight) = selection_totals(&inputs, &result.selected_inputs, &options).unwrap();
+    assert!(covers_target(&options, value, weight));
+
+    assert!(matches!(
+        select_coin_largest_first(&inputs, setup_options(200000)),
+        Err(SelectionError::InsufficientFunds)
+    ));
+}
+
+#[test]
+fn test_select_coin_smallest_first() {
+    let inputs = setup_bnb_sample_output_groups();
+    let mut options = setup_options(30000);
+    options.long_term_feerate = Some(FeeRate::from_sat_per_kwu(5000));
+    let result = select_coin_smallest_first(&inputs, options).unwrap();
+    // The 400 and 600 dust coins go first, then the inputs in ascending value.
+    assert_eq!(result.selected_inputs, vec![1, 5, 7, 3]);
+    assert_eq!(result.chosen_algorithm, Algorithm::SmallestFirst);
+    // Spending now at 500 sat/kWU instead of 5000 sat/kWU later saves more than the drain costs.
+    assert!(result.waste.0 < 0, "{result}");
+
+    // Inputs costing more than their value to spend are never selected.
+    options.target_feerate = FeeRate::from_sat_per_kwu(2500);
+    let result = select_coin_smallest_first(&inputs, options).unwrap();
+    assert!(!result.selected_inputs.contains(&InputIndex(1)));
+    assert!(!result.selected_inputs.contains(&InputIndex(5)));
+
+    assert!(matches!(
+        select_coin_smallest_first(&inputs, setup_options(200000)),
+        Err(SelectionError::InsufficientFunds)
+    ));
+}
+
+#[test]
+fn test_inputs_near_u64_max() {
+    let inputs = OutputGroup::from_utxos(&[(u64::MAX - 10, 20, false), (u64::MAX - 10, 20, false)]);
+    // Only both inputs together cover the target, and their values sum past u64::MAX.
+    let options = setup_options(u64::MAX - 100);
+    let mut rng = StdRng::seed_from_u64(268);
+    assert!(matches!(
+        selection_totals(&inputs, &[0usize, 1], &options),
+        Err(SelectionError::Overflow)
+    ));
+    assert!(matches!(
+        select_coin_bnb(&inputs, options, &mut rng),
+        Err(SelectionError::Overflow)
+    ));
+    // The saturated sum of effective values lies above the match window.
+    assert!(matches!(
+        select_coin_bnb_deterministic(&inputs, options),
+        Err(SelectionError::NoSolutionFound)
+    ));
+    assert!(matches!(
+        select_coin(&inputs, options, &mut rng),
+        Err(SelectionError::Overflow)
+    ));
+}
+
+#[test]
+fn test_uneconomical_input_excluded() {
+    let inputs =
+        OutputGroup::from_utxos(&[(6000, 100, false), (3000, 400, false), (4000, 100, false)]);
+    let mut options = setup_options(0);
+    options.target_feerate = FeeRate::from_sat_per_kwu(10000);
+    // Spending the second input costs 4000 sats, 1000 more than it is worth.
+    assert_eq!(effective_value(&inputs[1], &options), -1000);
+    assert!(!is_economical(&inputs[1], &options));
+
+    // Only the two economical inputs together reach the match window.
+    let window = MatchParameters::from_options(&options).unwrap();
+    options.target_value = 5000 + 3000 - window.target_for_match();
+    let mut rng = StdRng::seed_from_u64(270);
+    let mut selected = select_coin_bnb(&inputs, options, &mut rng)
+        .unwrap()
+        .selected_inputs;
+    selected.sort();
+    assert_eq!(selected, vec![InputIndex(0), InputIndex(2)]);
+
+    for target_value in [2000, 6500] {
+        options.target_value = target_value;
+        for result in [
+            select_coin_lowestlarger(&inputs, options),
+            select_coin_largest_first(&inputs, options),
+            select_coin_smallest_first(&inputs, options),
+            select_coin_knapsack(&inputs, options, &mut rng),
+        ] {
+            let result = result.unwrap();
+            assert!(!result.selected_inputs.contains(&InputIndex(1)), "{result}");
+        }
+    }
+}
+
+#[test]
+fn test_selection_change_value() {
+    let inputs = setup_basic_output_groups();
+    let mut options = setup_options(2500);
+    // The change is what is left once the fee for the whole transaction, drain included, is paid.
+    let checked_change = |selection: &SelectionOutput, options: &CoinSelectionOpt| {
+        let (value, weight) =
+            selection_totals(&inputs, &selection.selected_inputs, options).unwrap();
+        let weight = weight + options.base_weight + options.drain_weight;
+        let change = selection.change_value.unwrap();
+        assert_eq!(
+            value - options.target_value - change,
+            calculate_fee(weight, options.target_feerate),
+            "{selection}"
+        );
+        change
+    };
+    let mut rng = StdRng::seed_from_u64(271);
+    for selection in [
+        select_coin_fifo(&inputs, options),
+        select_coin_largest_first(&inputs, options),
+        select_coin_smallest_first(&inputs, options),
+        select_coin_knapsack(&inputs, options, &mut rng),
+        select_coin_srd(&inputs, options, &mut rng),
+    ] {
+        checked_change(&selection.unwrap(), &options);
+    }
+    let selection = select_coin_lowestlarger(&inputs, options).unwrap();
+    let change = checked_change(&selection, &options);
+    assert!(change >= options.min_drain_value);
+    assert!(selection.to_string().contains(&format!("change {change}")));
+
+    // Without a drain output the excess goes to the fee or the recipient.
+    for excess_strategy in [ExcessStrategy::ToFee, ExcessStrategy::ToRecipient] {
+        options.excess_strategy = excess_strategy;
+        let selection = select_coin_lowestlarger(&inputs, options).unwrap();
+        assert_eq!(selection.change_value, None);
+    }
+
+    // Change below min_drain_value paid as fee leaves no drain output.
+    options.excess_strategy = ExcessStrategy::ToDrain;
+    options.sub_dust_policy = SubDustPolicy::ToFee;
+    options.min_drain_value = change + 1;
+    let selection = select_coin_lowestlarger(&inputs, options).unwrap();
+    assert_eq!(selection.change_value, None);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_round_trip() {
+    let mut options = setup_options(5000);
+    options.long_term_feerate = Some(FeeRate::from_sat_per_kwu(100));
+    options.excess_strategy = ExcessStrategy::ToRecipient;
+    options.anchor = Some((1000, 200));
+    options.min_efficiency = Some(0.5);
+    let json = serde_json::to_string(&options).unwrap();
+    let decoded: CoinSelectionOpt = serde_json::from_str(&json).unwrap();
+    assert_eq!(format!("{decoded:?}"), format!("{options:?}"));
+
+    let inputs = setup_output_groups_withsequence();
+    let mut groups = inputs.clone();
+    groups[0].creation_sequence = None;
+    for group in groups {
+        let json = serde_json::to_string(&group).unwrap();
+        let decoded: OutputGroup = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.creation_sequence, group.creation_sequence);
+        assert_eq!(format!("{decoded:?}"), format!("{group:?}"));
+    }
+
+    assert_eq!(serde_json::to_string(&WasteMetric(-250)).unwrap(), "-250");
+    let selection = select_coin_fifo(&inputs, setup_options(2500)).unwrap();
+    let json = serde_json::to_string(&selection).unwrap();
+    let decoded: SelectionOutput = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, selection);
+    assert_eq!(decoded.change_value, selection.change_value);
+}
+
+#[cfg(feature = "bitcoin")]
+#[test]
+fn test_output_group_from_txout() {
+    let txout = bitcoin::TxOut {
+        value: bitcoin::Amount::from_sat(25_000),
+        script_pubkey: bitcoin::ScriptBuf::new(),
+    };
+    let group = OutputGroup::from_txout(&txout, bitcoin::Weight::from_wu(272), true, Some(3));
+    assert_eq!(group.value, 25_000);
+    assert_eq!(group.weight, 272);
+    assert_eq!(group.input_count, 1);
+    assert!(group.is_segwit);
+    assert_eq!(group.creation_sequence, Some(3));
+
+    let heavy = OutputGroup::from_txout(&txout, bitcoin::Weight::MAX, false, None);
+    assert_eq!(heavy.weight, u32::MAX);
+    assert_eq!(heavy.creation_sequence, None);
+}
+
+#[test]
+fn test_select_coin_single_lowest_larger() {
+    let inputs = setup_bnb_sample_output_groups();
+    let result = select_coin_single_lowest_larger(&inputs, setup_options(5730)).unwrap();
+    // 5000 does not cover the target on its own; 25000 is the smallest input that does.
+    assert_eq!(result.selected_inputs, vec![InputIndex(3)]);
+    assert_eq!(result.chosen_algorithm, Algorithm::SingleLowestLarger);
+
+    let result = select_coin_single_lowest_larger(&inputs, setup_options(4000)).unwrap();
+    assert_eq!(result.selected_inputs, vec![InputIndex(7)]);
+
+    // Every input together would cover it, but no single one does.
+    assert!(matches!(
+        select_coin_single_lowest_larger(&inputs, setup_options(60000)),
+        Err(SelectionError::NoSolutionFound)
+    ));
+}
+
+#[test]
+fn test_bnb_reports_iterations() {
+    let inputs = setup_bnb_sample_output_groups();
+    let options = setup_options(5730);
+    let selection = select_coin_bnb(&inputs, options, &mut StdRng::seed_from_u64(275)).unwrap();
+    assert_eq!(selection.chosen_algorithm, Algorithm::Bnb);
+    assert!((1..=BNB_TRIES).contains(&selection.iterations));
+
+    // The deterministic search explores the whole tree of these 8 inputs.
+    let deterministic = select_coin_bnb_deterministic(&inputs, options).unwrap();
+    assert!(deterministic.iterations >= selection.iterations);
+    assert!(deterministic.iterations < BNB_TRIES);
+
+    let fifo = select_coin_fifo(&inputs, options).unwrap();
+    assert_eq!(fifo.iterations, 0);
+}
+
+#[test]
+fn test_bnb_search_budget_exhausted() {
+    // Effective values 950, 1900 and 2850: the whole tree holds no match.
+    let options = setup_options(2975);
+    assert!(matches!(
+        select_coin_bnb_deterministic(&setup_basic_output_groups(), options),
+        Err(SelectionError::NoSolutionFound)
+    ));
+
+    // 40 inputs of effective value 950 never land in the window either, but there are far too
+    // many subsets to try them all.
+    let inputs = OutputGroup::from_utxos(&[(1000, 100, false); 40]);
+    let mut options = setup_options(0);
+    options.target_value = 950 * 20 + 100
+        - MatchParameters::from_options(&options)
+            .unwrap()
+            .target_for_match();
+    assert!(matches!(
+        select_coin_bnb_deterministic(&inputs, options),
+        Err(SelectionError::SearchBudgetExhausted)
+    ));
+    // select_coin_bnb still falls back to Single Random Draw.
+    let selection = select_coin_bnb(&inputs, options, &mut StdRng::seed_from_u64(276)).unwrap();
+    assert_eq!(selection.chosen_algorithm, Algorithm::Srd);
+}
+
+#[test]
+fn test_configurable_bnb_tries() {
+    let inputs = setup_bnb_sample_output_groups();
+    let options = setup_options(5730);
+    assert_eq!(options.bnb_tries, None);
+    let full = select_coin_bnb_deterministic(&inputs, options).unwrap();
+
+    assert!(full.iterations > 1);
+
+    // A budget below what the full search needs cuts it short.
+    let limited = CoinSelectionOpt {
+        bnb_tries: Some(1),
+        ..options
+    };
+    assert!(matches!(
+        select_coin_bnb_deterministic(&inputs, limited),
+        Err(SelectionError::SearchBudgetExhausted)
+    ));
+    let larger = CoinSelectionOpt {
+        bnb_tries: Some(full.iterations),
+        ..options
+    };
+    assert_eq!(
+        select_coin_bnb_deterministic(&inputs, larger).unwrap(),
+        full
+    );
+
+    // Zero tries runs no search; select_coin_bnb falls back to Single Random Draw.
+    let no_search = CoinSelectionOptBuilder::new(5730, options.target_feerate)
+        .base_weight(options.base_weight)
+        .cost_per_input(options.cost_per_input)
+        .cost_per_output(options.cost_per_output)
+        .bnb_tries(0)
+        .build();
+    assert!(matches!(
+        select_coin_bnb_deterministic(&inputs, no_search),
+        Err(SelectionError::SearchBudgetExhausted)
+    ));
+    let mut rng = StdRng::seed_from_u64(277);
+    let selection = select_coin_bnb(&inputs, no_search, &mut rng).unwrap();
+    assert_eq!(selection.chosen_algorithm, Algorithm::Srd);
+    assert!(!changeless_solution_exists(&inputs, no_search, &mut rng));
+}
+
+#[test]
+fn test_select_coin_bnb_with_drain() {
+    let inputs = setup_bnb_sample_output_groups();
+    // Inputs 7, 5 and 1 hold 4975 + 475 + 300 = 5750 of effective value.
+    let options = setup_options(5710);
+    let without_drain = MatchParameters::from_options(&options).unwrap();
+    let with_drain = MatchParameters::from_options(&with_drain_weight(&options).unwrap()).unwrap();
+    // ceil((10 + 50) * 0.5) instead of ceil(10 * 0.5) for the base weight.
+    assert_eq!(
+        with_drain.target_for_match(),
+        without_drain.target_for_match() + 25
+    );
+    assert_eq!(with_drain.match_range(), without_drain.match_range());
+
+    let selection = select_coin_bnb_with_drain(&inputs, options, &mut StdRng::seed_from_u64(278));
+    let selection = selection.unwrap();
+    assert_eq!(selection.chosen_algorithm, Algorithm::Bnb);
+    let effective_value: u64 = selection
+        .selected_inputs
+        .iter()
+        .map(|&index| effective_value_checked(&inputs[index], options.target_feerate).unwrap())
+        .sum();
+    assert!(effective_value >= with_drain.target_for_match());
+    assert!(effective_value <= with_drain.target_for_match() + with_drain.match_range());
+
+    // Without a drain output the window is unchanged.
+    let to_fee = CoinSelectionOpt {
+        excess_strategy: ExcessStrategy::ToFee,
+        ..options
+    };
+    assert_eq!(
+        MatchParameters::from_options(&with_drain_weight(&to_fee).unwrap()).unwrap(),
+        MatchParameters::from_options(&to_fee).unwrap()
+    );
+
+    // No match: the SRD fallback pays for the drain weight once, so a single input, covering
+    // 5000 + 500 of change + 40 of fee for 20 + 10 + 50 weight units, suffices.
+    let inputs = OutputGroup::from_utxos(&[(5550, 20, false); 2]);
+    let options = setup_options(5000);
+    let selection =
+        select_coin_bnb_with_drain(&inputs, options, &mut StdRng::seed_from_u64(278)).unwrap();
+    assert_eq!(selection.chosen_algorithm, Algorithm::Srd);
+    assert_eq!(selection.selected_inputs.len(), 1);
+    assert_eq!(selection.change_value, Some(5550 - 5000 - 40));
+}
+
+#[test]
+fn test_insufficient_funds_for_fee() {
+    // 3000 of value but only 950 * 3 = 2850 of effective value.
+    let inputs = OutputGroup::from_utxos(&[(1000, 100, false); 3]);
+    let mut options = setup_options(2900);
+    let mut rng = StdRng::seed_from_u64(280);
+    assert!(matches!(
+        select_coin(&inputs, options, &mut rng),
+        Err(SelectionError::InsufficientFundsForFee)
+    ));
+
+    // At a lower feerate the same inputs pay for the target and the fee.
+    options.target_feerate = FeeRate::from_sat_per_kwu(100);
+    options.min_drain_value = 10;
+    assert!(select_coin(&inputs, options, &mut rng).is_ok());
+
+    assert!(matches!(
+        select_coin(&inputs, setup_options(3001), &mut rng),
+        Err(SelectionError::InsufficientFunds)
+    ));
+}
+
+#[test]
+fn test_compute_waste() {
+    let inputs = setup_basic_output_groups();
+    let mut options = setup_options(2500);
+    let selection = select_coin_lowestlarger(&inputs, options).unwrap();
+    assert_eq!(
+        compute_waste(&inputs, &selection.selected_inputs, &options),
+        selection.waste.0
+    );
+
+    // Inputs 0 and 2 weigh 410 with the base weight: the fee is 205 and the long-term fee 41,
+    // plus the drain cost.
+    options.long_term_feerate = Some(FeeRate::from_sat_per_kwu(100));
+    assert_eq!(compute_waste(&inputs, &[0usize, 2], &options), 164 + 10);
+    // Without a drain the excess of 4000 - 2500 - 205 is wasted instead.
+    options.excess_strategy = ExcessStrategy::ToFee;
+    assert_eq!(compute_waste(&inputs, &[0usize, 2], &options), 164 + 1295);
+
+    let huge = OutputGroup::from_utxos(&[(u64::MAX, 100, false); 2]);
+    assert_eq!(compute_waste(&huge, &[0usize, 1], &options), i64::MAX);
+}
+
+#[test]
+fn test_input_count_scales() {
+    // The group spends three inputs of the same total value and weight as two single inputs.
+    let mut inputs =
+        OutputGroup::from_utxos(&[(6000, 40, false), (3000, 20, false), (3000, 20, false)]);
+    let options = setup_options(5000);
+    let single_waste = compute_waste(&inputs, &[0usize], &options);
+    let single_balance = target_from_fraction(&inputs, &options, 1.0);
+    assert_eq!(single_waste, compute_waste(&inputs, &[1usize, 2], &options));
+
+    // Each input past the first costs `cost_per_input` more to spend, and is worth that less.
+    for input_count in 2..=4 {
+        inputs[0].input_count = input_count;
+        let extra_cost = options.cost_per_input * (input_count as u64 - 1);
+        assert_eq!(
+            compute_waste(&inputs, &[0usize], &options),
+            single_waste + extra_cost as i64
+        );
+        assert_eq!(
+            target_from_fraction(&inputs, &options, 1.0),
+            single_balance - extra_cost
+        );
+    }
+
+    // So the two single inputs waste less than the group.
+    inputs[0].input_count = 3;
+    let result = select_coin_optimal(&inputs, options).unwrap();
+    assert_eq!(result.selected_inputs, vec![1, 2]);
+    assert_eq!(result.waste.0, single_waste);
+
+    let group = SelectionOutput {
+        selected_inputs: vec![InputIndex(0)],
+        waste: WasteMetric(0),
+        audit: None,
+        chosen_algorithm: Algorithm::Optimal,
+        change_value: None,
+        iterations: 0,
+    };
+    let weights = QualityWeights {
+        waste: 0.0,
+        input_count: 1.0,
+        privacy: 0.0,
+    };
+    assert_eq!(quality_score(&group, &inputs, &options, weights), 3.0);
+}
+
+#[test]
+fn test_segwit_marker_weight() {
+    let legacy = OutputGroup::from_utxos(&[(3000, 200, false), (3000, 200, false)]);
+    let mixed = OutputGroup::from_utxos(&[(3000, 200, false), (3000, 200, true)]);
+    let both_segwit = OutputGroup::from_utxos(&[(3000, 200, true), (3000, 200, true)]);
+    let mut options = setup_options(2000);
+    options.target_feerate = FeeRate::from_sat_per_kwu(5000);
+
+    let (_, legacy_weight) = selection_totals(&legacy, &[0usize, 1], &options).unwrap();
+    let (_, mixed_weight) = selection_totals(&mixed, &[0usize, 1], &options).unwrap();
+    let (_, segwit_weight) = selection_totals(&both_segwit, &[0usize, 1], &options).unwrap();
+    assert_eq!(legacy_weight, 400);
+    // The marker and flag are paid once, however many segwit inputs are spent.
+    assert_eq!(mixed_weight, 400 + SEGWIT_MARKER_WEIGHT);
+    assert_eq!(segwit_weight, mixed_weight);
+
+    let legacy_result = select_coin_fifo(&legacy, options).unwrap();
+    let mixed_result = select_coin_fifo(&mixed, options).unwrap();
+    assert_eq!(
+        legacy_result.change_value.unwrap() - mixed_result.change_value.unwrap(),
+        calculate_fee(SEGWIT_MARKER_WEIGHT, options.target_feerate)
+    );
+
+    // Effective values leave the marker out, so a BnB match spending a segwit input must still
+    // cover it: 3060 exactly hits the legacy match target, and falls short of the segwit one.
+    let mut rng = StdRng::seed_from_u64(283);
+    let bnb_algorithm = |utxos: &[(u64, u32, bool)], rng: &mut StdRng| {
+        select_coin_bnb(&OutputGroup::from_utxos(utxos), options, rng)
+            .map(|result| result.chosen_algorithm)
+    };
+    assert!(matches!(
+        bnb_algorithm(&[(3060, 200, false)], &mut rng),
+        Ok(Algorithm::Bnb)
+    ));
+    assert!(!matches!(
+        bnb_algorithm(&[(3060, 200, true)], &mut rng),
+        Ok(Algorithm::Bnb)
+    ));
+    assert!(matches!(
+        bnb_algorithm(&[(3070, 200, true)], &mut rng),
+        Ok(Algorithm::Bnb)
+    ));
+
+    // Only selections spending a segwit input pay for the marker: a segwit candidate left out
+    // does not move the legacy match off the edge of the window.
+    let inputs = OutputGroup::from_utxos(&[(2000, 200, true), (3060, 200, false)]);
+    for result in [
+        select_coin_bnb(&inputs, options, &mut rng).unwrap(),
+        select_coin_bnb_deterministic(&inputs, options).unwrap(),
+    ] {
+        assert_eq!(result.chosen_algorithm, Algorithm::Bnb);
+        assert_eq!(result.selected_inputs, vec![1]);
+    }
+}
+
+proptest::proptest! {
+    #[test]
+    fn prop_bnb_match_covers_target(
+        utxos in proptest::collection::vec(
+            (1u64..100_000, 1u32..1_000, proptest::bool::ANY),
+            1..20,
+        ),
+        target in 1u64..200_000,
+        feerate in 0u64..5_000,
+        seed in proptest::num::u64::ANY,
+    ) {
+        let inputs = OutputGroup::from_utxos(&utxos);
+        let mut options = setup_options(target);
+        options.target_feerate = FeeRate::from_sat_per_kwu(feerate);
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        // Only a BnB match is bound to the window, the SRD fallback pays for a change output.
+        if let Ok(result) = select_coin_bnb(&inputs, options, &mut rng) {
+            if result.chosen_algorithm == Algorithm::Bnb {
+                let selected_effective_value: i128 = result
+                    .selected_inputs
+                    .iter()
+                    .map(|&index| effective_value(&inputs[index], &options))
+                    .sum();
+                let window = MatchParameters::from_options(&options).unwrap();
+                proptest::prop_assert!(
+                    selected_effective_value >= i128::from(window.target_for_match())
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_validate_options() {
+    let inputs = setup_basic_output_groups();
+    assert!(setup_options(2500).validate().is_ok());
+
+    let zero_target = setup_options(0);
+    assert!(matches!(
+        zero_target.validate(),
+        Err(SelectionError::InvalidOptions(OptionError::ZeroTarget))
+    ));
+    assert!(matches!(
+        select_coin_fifo(&inputs, zero_target),
+        Err(SelectionError::InvalidOptions(OptionError::ZeroTarget))
+    ));
+
+    // Change below its own cost is only a concern while the excess goes to a drain.
+    let mut cheap_drain = setup_options(2500);
+    cheap_drain.min_drain_value = cheap_drain.drain_cost - 1;
+    assert!(matches!(
+        select_coin_largest_first(&inputs, cheap_drain),
+        Err(SelectionError::InvalidOptions(
+            OptionError::MinDrainBelowDrainCost
+        ))
+    ));
+    cheap_drain.excess_strategy = ExcessStrategy::ToFee;
+    assert!(cheap_drain.validate().is_ok());
+
+    let mut no_outputs = setup_options(2500);
+    no_outputs.num_outputs = 0;
+    assert!(matches!(
+        select_coin_bnb(&inputs, no_outputs, &mut rand::thread_rng()),
+        Err(SelectionError::InvalidOptions(OptionError::ZeroOutputs))
+    ));
+
+    let invalid_option = |options: CoinSelectionOpt| match options.validate() {
+        Err(SelectionError::InvalidOptions(error)) => Some(error),
+        _ => None,
+    };
+    for fee_buffer_percent in [-1.0, f32::NAN, f32::INFINITY] {
+        let mut options = setup_options(2500);
+        options.fee_buffer_percent = fee_buffer_percent;
+        assert_eq!(
+            invalid_option(options),
+            Some(OptionError::InvalidFeeBuffer),
+            "{fee_buffer_percent}"
+        );
+    }
+    for min_efficiency in [-0.1, 1.5, f32::NAN] {
+        let mut options = setup_options(2500);
+        options.min_efficiency = Some(min_efficiency);
+        assert_eq!(
+            invalid_option(options),
+            Some(OptionError::InvalidMinEfficiency),
+            "{min_efficiency}"
+        );
+    }
+    for max_change_ratio in [-0.5, f32::NAN] {
+        let mut options = setup_options(2500);
+        options.max_change_ratio = Some(max_change_ratio);
+        assert_eq!(
+            invalid_option(options),
+            Some(OptionError::InvalidMaxChangeRatio),
+            "{max_change_ratio}"
+        );
+    }
+    let mut options = setup_options(2500);
+    options.fee_buffer_percent = 10.0;
+    options.min_efficiency = Some(1.0);
+    options.max_change_ratio = Some(f32::INFINITY);
+    assert_eq!(invalid_option(options), None);
+    assert!(matches!(
+        select_coin_srd(
+            &inputs,
+            CoinSelectionOpt {
+                fee_buffer_percent: f32::NAN,
+                ..options
+            },
+            &mut rand::thread_rng()
+        ),
+        Err(SelectionError::InvalidOptions(
+            OptionError::InvalidFeeBuffer
+        ))
+    ));
+}
+
+#[test]
+fn test_bnb_insufficient_funds_without_search() {
+    // 40 * 950 = 38000 of effective value, short of the target and the 5 sat base fee.
+    let inputs = OutputGroup::from_utxos(&[(1000, 100, false); 40]);
+    let mut options = setup_options(38000);
+    let mut rng = StdRng::seed_from_u64(286);
+    assert!(matches!(
+        select_coin_bnb_changeless(&inputs, options, &mut rng),
+        Err(SelectionError::InsufficientFunds)
+    ));
+    assert!(matches!(
+        select_coin_bnb(&inputs, options, &mut rng),
+        Err(SelectionError::InsufficientFunds)
+    ));
+
+    // The check needs no search budget; a short search would have run out of tries instead.
+    options.bnb_tries = Some(0);
+    assert!(matches!(
+        select_coin_bnb_changeless(&inputs, options, &mut rng),
+        Err(SelectionError::InsufficientFunds)
+    ));
+
+    // Beyond `max_depth` the candidates fall short, but every input together covers the target
+    // with change, so the SRD fallback still runs.
+    let mut options = setup_options(5000);
+    options.max_depth = Some(3);
+    assert!(matches!(
+        select_coin_bnb_changeless(&inputs, options, &mut rng),
+        Err(SelectionError::NoSolutionFound)
+    ));
+    let selection = select_coin_bnb(&inputs, options, &mut rng).unwrap();
+    assert_eq!(selection.chosen_algorithm, Algorithm::Srd);
+    assert!(selection.selected_inputs.len() > 3);
+}
+
+#[test]
+fn test_selection_output_clone() {
+    let inputs = setup_basic_output_groups();
+    let mut options = setup_options(2500);
+    options.with_audit = true;
+    let result = select_coin_fifo(&inputs, options).unwrap();
+    let cached = result.clone();
+    assert_eq!(cached, result);
+    assert_eq!(cached.audit, result.audit);
+    assert_eq!(cached.waste.clone(), result.waste);
+
+    // Equality compares every field, even those the waste order ignores.
+    let mut other = result.clone();
+    other.change_value = other.change_value.map(|change| change + 1);
+    assert_ne!(other, result);
+    assert!(other.cmp_by_waste(&result).is_eq());
+    let mut other = result.clone();
+    other.iterations += 1;
+    assert_ne!(other, result);
+    let mut other = result.clone();
+    other.audit = None;
+    assert_ne!(other, result);
+    assert_ne!(other.cmp(&result), core::cmp::Ordering::Equal);
+}
+
+#[test]
+fn test_bnb_solutions() {
+    let inputs = OutputGroup::from_utxos(&[
+        (1000, 100, false),
+        (2000, 100, false),
+        (3000, 100, false),
+        (4000, 100, false),
+        (5000, 100, false),
+    ]);
+    let mut options = setup_options(5990);
+    options.target_feerate = FeeRate::from_sat_per_kwu(0);
+
+    // {1000, 5000}, {2000, 4000} and {1000, 2000, 3000} each hit 6000.
+    let solutions: Vec<SelectionOutput> = bnb_solutions(&inputs, options).collect();
+    assert_eq!(solutions.len(), 3);
+    let mut input_sets: Vec<Vec<InputIndex>> = solutions
+        .iter()
+        .map(|solution| {
+            let mut input_set = solution.selected_inputs.clone();
+            input_set.sort();
+            input_set
+        })
+        .collect();
+    input_sets.sort();
+    input_sets.dedup();
+    assert_eq!(input_sets.len(), 3);
+    assert!(solutions
+        .windows(2)
+        .all(|pair| compare_selections(&inputs, &pair[0], &pair[1], &options).is_le()));
+    // The same selection, though the enumeration doesn't count tries.
+    let deterministic = select_coin_bnb_deterministic(&inputs, options).unwrap();
+    assert!(solutions[0].cmp_by_waste(&deterministic).is_eq());
+
+    assert_eq!(bnb_solutions(&inputs, setup_options(50000)).count(), 0);
+    assert_eq!(bnb_solutions(&inputs, setup_options(0)).count(), 0);
+}
+
+#[test]
+fn test_dust_inputs_filtered() {
+    // The two oldest inputs cost more to spend than they are worth.
+    let mut inputs = OutputGroup::from_utxos(&[
+        (100, 400, false),
+        (200, 400, false),
+        (1000, 100, false),
+        (3000, 100, false),
+        (4000, 100, false),
+    ]);
+    for (sequence, input) in inputs.iter_mut().enumerate() {
+        input.creation_sequence = Some(sequence as u32);
+    }
+    let mut options = setup_options(3000);
+    assert_eq!(dust_input_count(&inputs, &options), 2);
+
+    let result = select_coin_fifo(&inputs, options).unwrap();
+    assert_eq!(result.selected_inputs, vec![2, 3]);
+    let mut rng = StdRng::seed_from_u64(289);
+    let result = select_coin_srd(&inputs, options, &mut rng).unwrap();
+    assert!(!result.selected_inputs.contains(&InputIndex(0)));
+    assert!(!result.selected_inputs.contains(&InputIndex(1)));
+
+    // Raising the threshold drops the 1000 sat input as well.
+    options.dust_threshold = 1000;
+    assert_eq!(dust_input_count(&inputs, &options), 3);
+    let result = select_coin_fifo(&inputs, options).unwrap();
+    assert_eq!(result.selected_inputs, vec![3, 4]);
+}
+
+#[test]
+fn test_bnb_prunes_unreachable_branches() {
+    // 30 * 950 = 28500 of effective value, and no multiple of 950 in the 28015..=28045 window:
+    // the search has to prove that no subset matches.
+    let inputs = OutputGroup::from_utxos(&[(1000, 100, false); 30]);
+    let options = setup_options(28000);
+    let match_parameters = MatchParameters::from_options(&options).unwrap();
+    let candidates = bnb_candidates(&inputs, &options);
+    let tries_spent = |prune_unreachable: bool| {
+        let mut bnb_tries = BNB_TRIES;
+        let exhausted = bnb_search(
+            &candidates,
+            &mut Vec::new(),
+            0,
+            0,
+            &mut bnb_tries,
+            &match_parameters,
+            &[],
+            prune_unreachable,
+            || true,
+            |_| std::ops::ControlFlow::Continue(()),
+        );
+        (BNB_TRIES - bnb_tries, exhausted)
+    };
+
+    // Without pruning, every subset short of the target is a node to explore.
+    assert_eq!(tries_spent(false), (BNB_TRIES, true));
+    // With it, omitting any input leaves the target out of reach.
+    let (pruned_tries, exhausted) = tries_spent(true);
+    assert!(!exhausted);
+    assert!(pruned_tries <= 2 * 30, "{pruned_tries} tries");
+}
+
+#[test]
+fn test_select_coin_bnb_with_preselected() {
+    let inputs = OutputGroup::from_utxos(&[
+        (1000, 100, false),
+        (2000, 100, false),
+        (3000, 100, false),
+        (4000, 100, false),
+        (5000, 100, false),
+    ]);
+    let mut options = setup_options(5990);
+    options.target_feerate = FeeRate::from_sat_per_kwu(0);
+    let mut rng = StdRng::seed_from_u64(291);
+
+    // Of the matches hitting 6000, only {1000, 2000, 3000} spends the 3000 sat input.
+    let result =
+        select_coin_bnb_with_preselected(&inputs, options, &[InputIndex(2)], &mut rng).unwrap();
+    assert_eq!(result.chosen_algorithm, Algorithm::Bnb);
+    assert_eq!(result.selected_inputs[0], InputIndex(2));
+    let mut selected_inputs = result.selected_inputs.clone();
+    selected_inputs.sort();
+    assert_eq!(selected_inputs, vec![0, 1, 2]);
+
+    // 9000 of mandatory inputs is past the 6000..=6030 window.
+    assert!(matches!(
+        select_coin_bnb_with_preselected(
+            &inputs,
+            options,
+            &[InputIndex(3), InputIndex(4)],
+            &mut rng
+        ),
+        Err(SelectionError::NoSolutionFound)
+    ));
+
+    // Nothing reaches the 6500..=6530 window, and there is no fallback to a selection with change.
+    options.target_value = 6490;
+    assert!(matches!(
+        select_coin_bnb_with_preselected(&inputs, options, &[InputIndex(2)], &mut rng),
+        Err(SelectionError::NoSolutionFound)
+    ));
+    let result = select_coin_bnb(&inputs, options, &mut rng).unwrap();
+    assert_eq!(result.chosen_algorithm, Algorithm::Srd);
+}
+
+#[test]
+fn test_max_inputs() {
+    let mut inputs = OutputGroup::from_utxos(&[
+        (1000, 100, false),
+        (1000, 100, false),
+        (1000, 100, false),
+        (1000, 100, false),
+        (2000, 100, false),
+        (2000, 100, false),
+    ]);
+    for (sequence, input) in inputs.iter_mut().enumerate() {
+        input.creation_sequence = Some(sequence as u32);
+    }
+    let mut options = setup_options(3000);
+    options.target_feerate = FeeRate::from_sat_per_kwu(0);
+
+    // FIFO covers 3000 and the 500 sat change with the four oldest inputs.
+    let result = select_coin_fifo(&inputs, options).unwrap();
+    assert_eq!(result.selected_inputs.len(), 4);
+    options.max_inputs = Some(3);
+    assert!(matches!(
+        select_coin_fifo(&inputs, options),
+        Err(SelectionError::NoSolutionFound)
+    ));
+
+    // Branch and Bound skips the match spending all four 1000 sat inputs.
+    options.target_value = 3990;
+    options.max_inputs = None;
+    assert_eq!(bnb_solutions(&inputs, options).count(), 14);
+    options.max_inputs = Some(3);
+    let solutions: Vec<SelectionOutput> = bnb_solutions(&inputs, options).collect();
+    assert_eq!(solutions.len(), 13);
+    assert!(solutions
+        .iter()
+        .all(|solution| solution.selected_inputs.len() <= 3));
+}
+
+#[test]
+fn test_select_coin_rbf() {
+    let inputs = OutputGroup::from_utxos(&[
+        (3000, 100, false),
+        (2000, 100, false),
+        (1000, 100, false),
+        (5000, 100, false),
+    ]);
+    let previous_selection = [InputIndex(0), InputIndex(1)];
+    let mut options = setup_options(4000);
+    // The original paid 200 sats.
+    options.min_absolute_fee = 200;
+    let total_value = |selection: &SelectionOutput| -> u64 {
+        selection
+            .selected_inputs
+            .iter()
+            .map(|&index| inputs[index].value)
+            .sum()
+    };
+    let fee_paid = |selection: &SelectionOutput| {
+        total_value(selection) - options.target_value - selection.change_value.unwrap_or(0)
+    };
+
+    // At 500 sat/kWU the previous inputs still cover the target, but must pay one sat more.
+    let bumped = select_coin_rbf(&inputs, options, &previous_selection).unwrap();
+    assert_eq!(bumped.selected_inputs, vec![0, 1]);
+    assert!(fee_paid(&bumped) > 200);
+
+    // At 10 sat/WU the previous inputs pay 2000 sats to spend, so the largest input is added.
+    options.target_feerate = FeeRate::from_sat_per_kwu(10_000);
+    let bumped = select_coin_rbf(&inputs, options, &previous_selection).unwrap();
+    assert_eq!(bumped.selected_inputs, vec![0, 1, 3]);
+    assert!(fee_paid(&bumped) >= 3000);
+}
+
+#[test]
+fn test_multiple_recipient_outputs() {
+    let inputs =
+        OutputGroup::from_utxos(&[(2000, 100, false), (1150, 100, false), (500, 100, false)]);
+    let mut options = setup_options(3000);
+
+    // A single recipient output costs 10 sats, so no subset lands in [3015, 3045].
+    let match_parameters = MatchParameters::from_options(&options).unwrap();
+    assert_eq!(match_parameters.target_for_match(), 3000 + 5 + 10);
+    assert!(select_coin_bnb(&inputs, options, &mut rand::thread_rng()).is_err());
+
+    // Three recipients pay 10 sats each, moving the window to [3035, 3065].
+    options.num_outputs = 3;
+    let match_parameters = MatchParameters::from_options(&options).unwrap();
+    assert_eq!(match_parameters.target_for_match(), 3000 + 5 + 3 * 10);
+    let result = select_coin_bnb(&inputs, options, &mut rand::thread_rng()).unwrap();
+    assert_eq!(result.selected_inputs, vec![0, 1]);
+    let effective_value: u64 = result
+        .selected_inputs
+        .iter()
+        .map(|&index| effective_value_checked(&inputs[index], options.target_feerate).unwrap())
+        .sum();
+    assert!(effective_value >= match_parameters.target_for_match());
+}
@@ -0,0 +1,938 @@
+//! The inputs, options and results of a selection.
+
+use alloc::vec::Vec;
+
+use rand::{rngs::StdRng, SeedableRng};
+
+/// A [`OutputGroup`] represents an input candidate for Coinselection. This can either be a
+/// single UTXO, or a group of UTXOs that should be spent together.
+/// The library user is responsible for crafting this structure correctly. Incorrect representation of this
+/// structure will cause incorrect selection result.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OutputGroup {
+    /// Total value of the UTXO(s) that this [`WeightedValue`] represents.
+    pub value: u64,
+    /// Total weight of including this/these UTXO(s).
+    /// `txin` fields: `prevout`, `nSequence`, `scriptSigLen`, `scriptSig`, `scriptWitnessLen`,
+    /// `scriptWitness` should all be included.
+    pub weight: u32,
+    /// The total number of inputs; so we can calculate extra `varint` weight due to `vin` length changes.
+    /// Selections are compared by the number of inputs they spend, counting each of a group's, and
+    /// each input past the first costs [`CoinSelectionOpt::cost_per_input`] on top of the fee.
+    pub input_count: usize,
+    /// Whether this [`OutputGroup`] contains at least one segwit spend.
+    pub is_segwit: bool,
+    /// Relative Creation sequence for this group. Only used for FIFO selection. Specify None, if FIFO
+    /// selection is not required.
+    /// sequence numbers are arbitrary index only to denote relative age of utxo group among a set of groups.
+    /// To denote the oldest utxo group, give them a sequence number of Some(0).
+    pub creation_sequence: Option<u32>,
+    /// Whether this group may only be spent in a changeless transaction, as required by some
+    /// privacy protocols. Only Branch and Bound matches use it while the excess goes to a drain.
+    pub changeless_only: bool,
+    /// The pool this group belongs to, see [`select_coin_from_pools`](crate::select_coin_from_pools).
+    pub pool: Pool,
+}
+
+/// Whether an [`OutputGroup`] is meant for everyday spending or kept in reserve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Pool {
+    #[default]
+    Spending,
+    /// Only spent when the spending pool cannot cover the target.
+    Reserve,
+}
+
+impl OutputGroup {
+    /// Create an [`OutputGroup`] for a single UTXO, without a creation sequence.
+    pub fn single(value: u64, weight: u32, is_segwit: bool) -> OutputGroup {
+        OutputGroup {
+            value,
+            weight,
+            input_count: 1,
+            is_segwit,
+            creation_sequence: None,
+            changeless_only: false,
+            pool: Pool::Spending,
+        }
+    }
+
+    /// Create one single-UTXO [`OutputGroup`] per `(value, weight, is_segwit)` entry.
+    /// The returned groups keep the order of `utxos`, so selected indices refer back to it.
+    pub fn from_utxos(utxos: &[(u64, u32, bool)]) -> Vec<OutputGroup> {
+        utxos
+            .iter()
+            .map(|&(value, weight, is_segwit)| OutputGroup::single(value, weight, is_segwit))
+            .collect()
+    }
+
+    /// Create an [`OutputGroup`] for a single UTXO from its `bitcoin` output and the weight of
+    /// the input spending it. A weight above `u32::MAX` saturates, which no transaction fits.
+    #[cfg(feature = "bitcoin")]
+    pub fn from_txout(
+        txout: &bitcoin::TxOut,
+        weight: bitcoin::Weight,
+        is_segwit: bool,
+        creation_sequence: Option<u32>,
+    ) -> OutputGroup {
+        OutputGroup {
+            creation_sequence,
+            ..OutputGroup::single(
+                txout.value.to_sat(),
+                u32::try_from(weight.to_wu()).unwrap_or(u32::MAX),
+                is_segwit,
+            )
+        }
+    }
+}
+
+/// An amount type selection can run on, e.g. a millisat newtype for Lightning-adjacent flows.
+/// Plain `u64` sats are the default used throughout the crate.
+pub trait Amount: Copy {
+    /// Number of the amount's base units per sat, e.g. 1000 for millisats.
+    const UNITS_PER_SAT: u64;
+
+    /// The amount in base units.
+    fn to_units(self) -> u64;
+
+    /// The amount of `units` base units.
+    fn from_units(units: u64) -> Self;
+}
+
+impl Amount for u64 {
+    const UNITS_PER_SAT: u64 = 1;
+
+    fn to_units(self) -> u64 {
+        self
+    }
+
+    fn from_units(units: u64) -> Self {
+        units
+    }
+}
+
+/// Where the randomized selectors get their randomness from, so a service can log the source and
+/// replay a selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedSource<'a> {
+    /// Fresh entropy from the operating system; selections are not reproducible.
+    /// Only with the `std` feature.
+    #[cfg(feature = "std")]
+    Os,
+    /// A fixed seed.
+    Fixed(u64),
+    /// A seed derived from arbitrary bytes, e.g. a request id, with FNV-1a.
+    Derived(&'a [u8]),
+}
+
+impl SeedSource<'_> {
+    /// The RNG for this source.
+    pub fn rng(&self) -> StdRng {
+        self.rng_for_stream(0)
+    }
+
+    /// An independent RNG for the `stream`th consumer of this source, e.g. one per thread.
+    /// Stream 0 is the RNG returned by [`SeedSource::rng`].
+    pub fn rng_for_stream(&self, stream: u64) -> StdRng {
+        let seed = match *self {
+            #[cfg(feature = "std")]
+            SeedSource::Os => return StdRng::from_entropy(),
+            SeedSource::Fixed(seed) => seed,
+            SeedSource::Derived(bytes) => {
+                bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+                    (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+                })
+            }
+        };
+        StdRng::seed_from_u64(seed.wrapping_add(stream))
+    }
+}
+
+/// The index of an input in the slice of [`OutputGroup`]s passed to a selector, as opposed to an
+/// index into some filtered or reordered copy of it.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InputIndex(pub usize);
+
+// Printed as the bare index, so selections read the same as before the newtype.
+impl core::fmt::Debug for InputIndex {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl PartialEq<usize> for InputIndex {
+    fn eq(&self, other: &usize) -> bool {
+        self.0 == *other
+    }
+}
+
+impl From<InputIndex> for usize {
+    fn from(index: InputIndex) -> usize {
+        index.0
+    }
+}
+
+impl core::fmt::Display for InputIndex {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl core::ops::Index<InputIndex> for [OutputGroup] {
+    type Output = OutputGroup;
+
+    fn index(&self, index: InputIndex) -> &OutputGroup {
+        &self[index.0]
+    }
+}
+
+impl core::ops::Index<InputIndex> for Vec<OutputGroup> {
+    type Output = OutputGroup;
+
+    fn index(&self, index: InputIndex) -> &OutputGroup {
+        &self[index.0]
+    }
+}
+
+/// A set of Options that guides the CoinSelection algorithms. These are inputs specified by the
+/// user to perform coinselection to achieve a set a target parameters.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoinSelectionOpt {
+    /// The value we need to select.
+    pub target_value: u64,
+
+    /// The feerate we should try and achieve.
+    pub target_feerate: FeeRate,
+    /// The feerate
+    pub long_term_feerate: Option<FeeRate>, // TODO: Maybe out of scope? (waste)
+    /// The minimum absolute fee. I.e., needed for RBF.
+    /// Selections pay the larger of this and the fee at `target_feerate`.
+    pub min_absolute_fee: u64,
+
+    /// The weight of the template transaction, including fixed fields and outputs.
+    pub base_weight: u32,
+    /// Additional weight if we include the drain (change) output.
+    /// Not priced into the waste, which counts the drain through `drain_cost` only.
+    pub drain_weight: u32,
+
+    /// Total cost of the drain (change) output in sats: the fee for `drain_weight` at
+    /// `target_feerate` to create it, plus the fee to spend it in the future.
+    pub drain_cost: u64,
+
+    /// Estimate of cost of spending an input. Sizes the Branch and Bound match window as the cost
+    /// of one more input, and is charged for each input of a group past its first: the group's
+    /// effective value is that much lower and its waste that much higher.
+    pub cost_per_input: u64,
+
+    /// Estimate of cost of spending the output, paid for each of the `num_outputs`
+    pub cost_per_output: u64,
+
+    /// The number of recipient outputs, at least 1 and 1 by default. Branch and Bound matches pay
+    /// `cost_per_output` for each; their weight belongs in `base_weight` like any output's.
+    pub num_outputs: usize,
+
+    /// Minimum value allowed for a drain (change) output.
+    pub min_drain_value: u64,
+
+    /// Strategy to use the excess value other than fee and target
+    pub excess_strategy: ExcessStrategy,
+
+    /// Forbid a change output: an overshoot up to this value is paid as fee, a larger one
+    /// rejects the selection as too wasteful. `None` allows change as usual.
+    pub max_excess_to_fee: Option<u64>,
+
+    /// Limit the Branch and Bound search to the `max_depth` highest value inputs, bounding its
+    /// latency on large sets. This caps how many candidates are considered, not how many inputs
+    /// a selection may contain; a selection can still use every considered input, see
+    /// [`CoinSelectionOpt::max_inputs`] for that.
+    pub max_depth: Option<usize>,
+
+    /// Search budget of the Branch and Bound algorithms: the number of tries an attempt may
+    /// spend, 1,000,000 if `None`. Raise it for large sets, lower it on latency-sensitive paths.
+    /// `Some(0)` disables the search, so Branch and Bound only takes a match needing no search,
+    /// and otherwise fails or falls back as if the budget were exhausted.
+    pub bnb_tries: Option<u32>,
+
+    /// Reject selections spending more than this many inputs, counting each of a group's, e.g.
+    /// to keep signing on a hardware wallet fast. Inputs forced in through the anchor are not
+    /// counted. Branch and Bound prunes branches past the cap.
+    pub max_inputs: Option<usize>,
+
+    /// Summed coin age the selection should come closest to, used by [`select_coin_coin_age`](crate::select_coin_coin_age).
+    pub target_coin_age: Option<u64>,
+
+    /// Record in [`SelectionOutput::audit`] why each input was selected.
+    pub with_audit: bool,
+
+    /// Value and weight already included in the package, i.e. the anchor output of a parent
+    /// transaction being bumped via CPFP. The selection only needs to top up to the target.
+    pub anchor: Option<(u64, u32)>,
+
+    /// Exclude inputs whose effective value is less than this fraction of their value, e.g.
+    /// `0.5` skips inputs spending more than half their value on fees.
+    pub min_efficiency: Option<f32>,
+
+    /// Leave out inputs of at most this value, on top of those worth no more than the fee to
+    /// spend them at `target_feerate`, which are always left out. Zero only drops the latter.
+    pub dust_threshold: u64,
+
+    /// What to do when the change would be below `min_drain_value`.
+    pub sub_dust_policy: SubDustPolicy,
+
+    /// Reject selections whose change exceeds this multiple of `target_value`, steering towards
+    /// tighter selections.
+    pub max_change_ratio: Option<f32>,
+
+    /// Inflate the required fee by this percentage, as a safety margin for signed weights
+    /// exceeding the estimates. `0.0` selects for the nominal fee.
+    pub fee_buffer_percent: f32,
+
+    /// Prefer spending whole clusters, i.e. groups with an `input_count` above one, to leave no
+    /// fragments behind. This is a preference, not a rule: it only decides between candidate
+    /// selections of equal waste.
+    pub prefer_clusters: bool,
+
+    /// How the waste of a selection is computed. [`WasteModel::Legacy`] keeps the waste
+    /// figures of earlier releases.
+    pub waste_model: WasteModel,
+}
+
+impl CoinSelectionOpt {
+    /// Check that the options make sense together; every selector does so before selecting.
+    /// The feerates are unsigned, so they need no check.
+    /// Return InvalidOptions, naming the first inconsistency found.
+    pub fn validate(&self) -> Result<(), SelectionError> {
+        let invalid = |error| Err(SelectionError::InvalidOptions(error));
+        if self.target_value == 0 {
+            return invalid(OptionError::ZeroTarget);
+        }
+        if self.num_outputs == 0 {
+            return invalid(OptionError::ZeroOutputs);
+        }
+        if self.excess_strategy == ExcessStrategy::ToDrain && self.min_drain_value < self.drain_cost
+        {
+            return invalid(OptionError::MinDrainBelowDrainCost);
+        }
+        if !(self.fee_buffer_percent.is_finite() && self.fee_buffer_percent >= 0.0) {
+            return invalid(OptionError::InvalidFeeBuffer);
+        }
+        if self
+            .min_efficiency
+            .is_some_and(|min_efficiency| !(0.0..=1.0).contains(&min_efficiency))
+        {
+            return invalid(OptionError::InvalidMinEfficiency);
+        }
+        if self
+            .max_change_ratio
+            .is_some_and(|max_change_ratio| max_change_ratio.is_nan() || max_change_ratio < 0.0)
+        {
+            return invalid(OptionError::InvalidMaxChangeRatio);
+        }
+        Ok(())
+    }
+}
+
+/// Builds a [`CoinSelectionOpt`] from the target value and feerate, which are required.
+/// Every other field defaults to zero, `None` or `false`, with one recipient output, excess going
+/// to a drain output, [`SubDustPolicy::ForceMoreInputs`] and [`WasteModel::Consolidated`].
+#[derive(Debug, Clone, Copy)]
+pub struct CoinSelectionOptBuilder {
+    options: CoinSelectionOpt,
+}
+
+impl CoinSelectionOptBuilder {
+    pub fn new(target_value: u64, target_feerate: FeeRate) -> Self {
+        CoinSelectionOptBuilder {
+            options: CoinSelectionOpt {
+                target_value,
+                target_feerate,
+                long_term_feerate: None,
+                min_absolute_fee: 0,
+                base_weight: 0,
+                drain_weight: 0,
+                drain_cost: 0,
+                cost_per_input: 0,
+                cost_per_output: 0,
+                num_outputs: 1,
+                min_drain_value: 0,
+                excess_strategy: ExcessStrategy::ToDrain,
+                max_excess_to_fee: None,
+                max_depth: None,
+                bnb_tries: None,
+                max_inputs: None,
+                target_coin_age: None,
+                with_audit: false,
+                anchor: None,
+                min_efficiency: None,
+                dust_threshold: 0,
+                sub_dust_policy: SubDustPolicy::ForceMoreInputs,
+                max_change_ratio: None,
+                fee_buffer_percent: 0.0,
+                prefer_clusters: false,
+                waste_model: WasteModel::Consolidated,
+            },
+        }
+    }
+
+    /// Set [`CoinSelectionOpt::long_term_feerate`].
+    pub fn long_term_feerate(mut self, long_term_feerate: FeeRate) -> Self {
+        self.options.long_term_feerate = Some(long_term_feerate);
+        self
+    }
+
+    /// Set [`CoinSelectionOpt::min_absolute_fee`].
+    pub fn min_absolute_fee(mut self, min_absolute_fee: u64) -> Self {
+        self.options.min_absolute_fee = min_absolute_fee;
+        self
+    }
+
+    /// Set [`CoinSelectionOpt::base_weight`].
+    pub fn base_weight(mut self, base_weight: u32) -> Self {
+        self.options.base_weight = base_weight;
+        self
+    }
+
+    /// Set [`CoinSelectionOpt::drain_weight`].
+    pub fn drain_weight(mut self, drain_weight: u32) -> Self {
+        self.options.drain_weight = drain_weight;
+        self
+    }
+
+    /// Set [`CoinSelectionOpt::drain_cost`].
+    pub fn drain_cost(mut self, drain_cost: u64) -> Self {
+        self.options.drain_cost = drain_cost;
+        self
+    }
+
+    /// Set [`CoinSelectionOpt::cost_per_input`].
+    pub fn cost_per_input(mut self, cost_per_input: u64) -> Self {
+        self.options.cost_per_input = cost_per_input;
+        self
+    }
+
+    /// Set [`CoinSelectionOpt::cost_per_output`].
+    pub fn cost_per_output(mut self, cost_per_output: u64) -> Self {
+        self.options.cost_per_output = cost_per_output;
+        self
+    }
+
+    /// Set [`CoinSelectionOpt::num_outputs`].
+    pub fn num_outputs(mut self, num_outputs: usize) -> Self {
+        self.options.num_outputs = num_outputs;
+        self
+    }
+
+    /// Set [`CoinSelectionOpt::min_drain_value`].
+    pub fn min_drain_value(mut self, min_drain_value: u64) -> Self {
+        self.options.min_drain_value = min_drain_value;
+        self
+    }
+
+    /// Set [`CoinSelectionOpt::excess_strategy`].
+    pub fn excess_strategy(mut self, excess_strategy: ExcessStrategy) -> Self {
+        self.options.excess_strategy = excess_strategy;
+        self
+    }
+
+    /// Set [`CoinSelectionOpt::max_excess_to_fee`].
+    pub fn max_excess_to_fee(mut self, max_excess_to_fee: u64) -> Self {
+        self.options.max_excess_to_fee = Some(max_excess_to_fee);
+        self
+    }
+
+    /// Set [`CoinSelectionOpt::max_depth`].
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.options.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Set [`CoinSelectionOpt::bnb_tries`].
+    pub fn bnb_tries(mut self, bnb_tries: u32) -> Self {
+        self.options.bnb_tries = Some(bnb_tries);
+        self
+    }
+
+    /// Set [`CoinSelectionOpt::max_inputs`].
+    pub fn max_inputs(mut self, max_inputs: usize) -> Self {
+        self.options.max_inputs = Some(max_inputs);
+        self
+    }
+
+    /// Set [`CoinSelectionOpt::target_coin_age`].
+    pub fn target_coin_age(mut self, target_coin_age: u64) -> Self {
+        self.options.target_coin_age = Some(target_coin_age);
+        self
+    }
+
+    /// Set [`CoinSelectionOpt::with_audit`].
+    pub fn with_audit(mut self, with_audit: bool) -> Self {
+        self.options.with_audit = with_audit;
+        self
+    }
+
+    /// Set [`CoinSelectionOpt::anchor`].
+    pub fn anchor(mut self, anchor: (u64, u32)) -> Self {
+        self.options.anchor = Some(anchor);
+        self
+    }
+
+    /// Set [`CoinSelectionOpt::min_efficiency`].
+    pub fn min_efficiency(mut self, min_efficiency: f32) -> Self {
+        self.options.min_efficiency = Some(min_efficiency);
+        self
+    }
+
+    /// Set [`CoinSelectionOpt::dust_threshold`].
+    pub fn dust_threshold(mut self, dust_threshold: u64) -> Self {
+        self.options.dust_threshold = dust_threshold;
+        self
+    }
+
+    /// Set [`CoinSelectionOpt::sub_dust_policy`].
+    pub fn sub_dust_policy(mut self, sub_dust_policy: SubDustPolicy) -> Self {
+        self.options.sub_dust_policy = sub_dust_policy;
+        self
+    }
+
+    /// Set [`CoinSelectionOpt::max_change_ratio`].
+    pub fn max_change_ratio(mut self, max_change_ratio: f32) -> Self {
+        self.options.max_change_ratio = Some(max_change_ratio);
+        self
+    }
+
+    /// Set [`CoinSelectionOpt::fee_buffer_percent`].
+    pub fn fee_buffer_percent(mut self, fee_buffer_percent: f32) -> Self {
+        self.options.fee_buffer_percent = fee_buffer_percent;
+        self
+    }
+
+    /// Set [`CoinSelectionOpt::prefer_clusters`].
+    pub fn prefer_clusters(mut self, prefer_clusters: bool) -> Self {
+        self.options.prefer_clusters = prefer_clusters;
+        self
+    }
+
+    /// Set [`CoinSelectionOpt::waste_model`].
+    pub fn waste_model(mut self, waste_model: WasteModel) -> Self {
+        self.options.waste_model = waste_model;
+        self
+    }
+
+    pub fn build(self) -> CoinSelectionOpt {
+        self.options
+    }
+}
+
+/// A feerate in sats per 1000 weight units (sat/kWU).
+/// Kept as an integer so fees computed from it are exact and reproducible: two wallets running
+/// the same selection agree on every fee to the sat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FeeRate(u64);
+
+impl FeeRate {
+    /// Create a [`FeeRate`] from sats per 1000 weight units, as reported by many fee estimators.
+    pub fn from_sat_per_kwu(sat_per_kwu: u64) -> FeeRate {
+        FeeRate(sat_per_kwu)
+    }
+
+    /// The feerate in sats per 1000 weight units.
+    pub fn as_sat_per_kwu(&self) -> u64 {
+        self.0
+    }
+
+    /// The feerate in sats per weight unit, for display.
+    pub fn as_sat_per_wu(&self) -> f32 {
+        self.0 as f32 / 1000.0
+    }
+
+    /// The fee for `weight` at this feerate, rounded up to the next sat using integer math.
+    /// Saturates at `u64::MAX`, which no set of inputs can pay.
+    pub fn fee_for_weight(&self, weight: u32) -> u64 {
+        let fee = (u128::from(weight) * u128::from(self.0)).div_ceil(1000);
+        u64::try_from(fee).unwrap_or(u64::MAX)
+    }
+}
+
+/// Strategy to decide what to do with the excess amount.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExcessStrategy {
+    ToFee,
+    /// Pay the excess to the recipient; it is not lost, so it is not counted as waste.
+    ToRecipient,
+    ToDrain,
+}
+
+/// Policy for a selection whose change would fall below [`CoinSelectionOpt::min_drain_value`].
+/// Only applies while the excess goes to a drain output, and never to Branch and Bound matches,
+/// which have no change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SubDustPolicy {
+    /// Accept the selection and pay the change as fee instead of creating a drain output.
+    ToFee,
+    /// Return [`SelectionError::WouldCreateDust`].
+    Reject,
+    /// Keep adding inputs until the change reaches `min_drain_value`.
+    ForceMoreInputs,
+}
+
+/// How the waste of a selection is computed, see [`CoinSelectionOpt::waste_model`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WasteModel {
+    /// Bitcoin Core's waste as a single sum: the fee of the inputs at `target_feerate` minus
+    /// their fee at `long_term_feerate`, plus `drain_cost` with a drain output or the excess
+    /// without one. The sum is negative when spending now is cheaper than later by more than
+    /// the drain cost or excess, favouring consolidation at low feerates.
+    Consolidated,
+    /// The original metric, whose long-term term `fee - n * long_term_feerate * weight` scales
+    /// with the number of inputs and is clamped at zero before the drain cost or excess is added.
+    #[deprecated(note = "counts the long-term term inconsistently; use WasteModel::Consolidated")]
+    Legacy,
+}
+
+/// Error Describing failure of a selection attempt, on any subset of inputs
+#[derive(Debug)]
+pub enum SelectionError {
+    InsufficientFunds,
+    NoSolutionFound,
+    /// An intermediate amount exceeded `u64::MAX`.
+    Overflow,
+    /// Too many inputs for an exhaustive search, see [`select_coin_optimal`](crate::select_coin_optimal).
+    TooManyInputs,
+    /// The change would be below `min_drain_value`, see [`SubDustPolicy::Reject`].
+    WouldCreateDust,
+    /// No selection covering the target spends a confirmed input, see
+    /// [`select_coin_with_confirmed_input`](crate::select_coin_with_confirmed_input).
+    NoConfirmedInput,
+    /// The Branch and Bound search ran out of tries before it explored every branch; a match may
+    /// still exist. [`SelectionError::NoSolutionFound`] means the whole tree held none.
+    SearchBudgetExhausted,
+    /// The inputs' total value covers the target, but not once the fee to spend them is paid;
+    /// a lower feerate may allow a selection.
+    InsufficientFundsForFee,
+    /// The options are inconsistent, see [`CoinSelectionOpt::validate`].
+    InvalidOptions(OptionError),
+    /// Fewer risk scores than inputs were given, see
+    /// [`select_coin_min_risk`](crate::select_coin_min_risk).
+    MissingRiskScores,
+}
+
+/// An inconsistency in a [`CoinSelectionOpt`], reported by [`CoinSelectionOpt::validate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionError {
+    /// `target_value` is zero, so there is nothing to select.
+    ZeroTarget,
+    /// `num_outputs` is zero, so no recipient output receives the target.
+    ZeroOutputs,
+    /// The excess goes to a drain output, but `min_drain_value` is below `drain_cost`, allowing
+    /// change worth less than it costs to create and spend.
+    MinDrainBelowDrainCost,
+    /// `fee_buffer_percent` is negative, infinite or NaN.
+    InvalidFeeBuffer,
+    /// `min_efficiency` is not a fraction between 0 and 1, or is NaN.
+    InvalidMinEfficiency,
+    /// `max_change_ratio` is negative or NaN.
+    InvalidMaxChangeRatio,
+}
+
+/// Error returned by [`effective_value_checked`](crate::effective_value_checked) for an input that costs at least its value to
+/// spend.
+#[derive(Debug, PartialEq, Eq)]
+pub struct DustError {
+    /// The value of the input.
+    pub value: u64,
+    /// The fee to spend the input at the given feerate.
+    pub fee: u64,
+}
+
+/// Calculated waste for a specific selection.
+/// This is used to compare various selection algorithm and find the most
+/// optimizewd solution, represented by least [WasteMetric] value.
+/// Metrics order by their value, so selections from different runs can be compared directly.
+/// The waste is in the base units of the amounts selected: sats, or those of the [`Amount`] of
+/// [`select_coin_in`](crate::select_coin_in), so it is displayed without a unit.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WasteMetric(pub i64);
+
+/// The result of selection algorithm
+///
+/// Discarding a selection is almost always a mistake, so the selectors and this type are
+/// `#[must_use]`:
+///
+/// ```compile_fail
+/// #![deny(unused_must_use)]
+/// # use rust_coinselect::{select_coin_fifo, CoinSelectionOpt, OutputGroup};
+/// # fn discard(inputs: &[OutputGroup], options: CoinSelectionOpt) {
+/// select_coin_fifo(inputs, options);
+/// # }
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[must_use]
+pub struct SelectionOutput {
+    /// The selected input indices, refers to the indices of the inputs Slice Reference
+    pub selected_inputs: Vec<InputIndex>,
+    /// The waste amount, for the above inputs
+    pub waste: WasteMetric,
+    /// Why each selected input was chosen, if [`CoinSelectionOpt::with_audit`] is set.
+    pub audit: Option<Vec<(InputIndex, SelectionReason)>>,
+    /// The algorithm that produced the selection.
+    pub chosen_algorithm: Algorithm,
+    /// The value of the drain (change) output to create: the selected value minus the target and
+    /// the fee for the whole transaction at `target_feerate`, i.e. for the inputs, `base_weight`
+    /// and `drain_weight`, at least `min_absolute_fee`. None without a drain output, i.e. for Branch and Bound matches, when the
+    /// [`ExcessStrategy`] is not [`ExcessStrategy::ToDrain`], or when the excess is below
+    /// [`CoinSelectionOpt::min_drain_value`].
+    pub change_value: Option<u64>,
+    /// The number of tries the Branch and Bound search that produced the selection spent of its
+    /// budget; a figure near the budget means the search was cut short. Zero for the other
+    /// algorithms, and for a match found without searching.
+    pub iterations: u32,
+}
+
+impl core::fmt::Display for WasteMetric {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl core::fmt::Display for SelectionOutput {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{} inputs {:?}, waste {}, {:?}, ",
+            self.selected_inputs.len(),
+            self.selected_inputs,
+            self.waste,
+            self.chosen_algorithm,
+        )?;
+        match self.change_value {
+            Some(change_value) => write!(f, "change {change_value}"),
+            None => write!(f, "changeless"),
+        }
+    }
+}
+
+impl SelectionOutput {
+    /// Order selections by waste, lowest first, e.g. with `min_by` to pick the least waste one.
+    /// Ties go to fewer inputs, then to the lexicographically lowest `selected_inputs`, then to
+    /// the earlier [`Algorithm`] variant. The audit, change and iterations are not compared, so
+    /// selections that differ only in them are equivalent here but not equal.
+    pub fn cmp_by_waste(&self, other: &Self) -> core::cmp::Ordering {
+        let key = |selection: &Self| (selection.waste.0, selection.selected_inputs.len());
+        key(self)
+            .cmp(&key(other))
+            .then_with(|| self.selected_inputs.cmp(&other.selected_inputs))
+            .then_with(|| self.chosen_algorithm.cmp(&other.chosen_algorithm))
+    }
+}
+
+/// Selections are ordered by [`SelectionOutput::cmp_by_waste`], so `.min()` over a collection
+/// returns the least waste one. Ties are resolved by the change, the iterations and the audit,
+/// so the order agrees with equality.
+impl Ord for SelectionOutput {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.cmp_by_waste(other)
+            .then_with(|| self.change_value.cmp(&other.change_value))
+            .then_with(|| self.iterations.cmp(&other.iterations))
+            .then_with(|| self.audit.cmp(&other.audit))
+    }
+}
+
+impl PartialOrd for SelectionOutput {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl core::fmt::Debug for SelectionOutput {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SelectionOutput")
+            .field("input_count", &self.selected_inputs.len())
+            .field("selected_inputs", &self.selected_inputs)
+            .field("waste", &format_args!("{}", self.waste))
+            .field("chosen_algorithm", &self.chosen_algorithm)
+            .field("change_value", &self.change_value)
+            .field("iterations", &self.iterations)
+            .field("audit", &self.audit)
+            .finish()
+    }
+}
+
+/// The selection algorithms, as reported in [`SelectionOutput::chosen_algorithm`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Algorithm {
+    Bnb,
+    LowestLarger,
+    Fifo,
+    Srd,
+    RecentChange,
+    Optimal,
+    MinRisk,
+    AnchorTopup,
+    Knapsack,
+    LargestFirst,
+    SmallestFirst,
+    SingleLowestLarger,
+}
+
+impl Algorithm {
+    const ALL: [Algorithm; 12] = [
+        Algorithm::Bnb,
+        Algorithm::LowestLarger,
+        Algorithm::Fifo,
+        Algorithm::Srd,
+        Algorithm::RecentChange,
+        Algorithm::Optimal,
+        Algorithm::MinRisk,
+        Algorithm::AnchorTopup,
+        Algorithm::Knapsack,
+        Algorithm::LargestFirst,
+        Algorithm::SmallestFirst,
+        Algorithm::SingleLowestLarger,
+    ];
+}
+
+/// The reason an input was chosen, as recorded in [`SelectionOutput::audit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SelectionReason {
+    /// The caller required the input to be spent.
+    Forced,
+    /// The input had the highest effective value among the remaining candidates.
+    HighestEffectiveValue,
+    /// The input was added to accumulate enough value for the target and fee.
+    NeededToReachTarget,
+    /// The input was added to reach a minimum number of inputs for privacy.
+    PrivacyMinimum,
+}
+
+/// Error Describing failure to decode a [`SelectionOutput`] from bytes.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The bytes ended in the middle of a value.
+    UnexpectedEnd,
+    /// A varint does not fit in the integer it encodes.
+    VarIntOverflow,
+    /// Bytes were left over after the selection was decoded.
+    TrailingBytes,
+    /// The algorithm is not one of [`Algorithm`].
+    UnknownAlgorithm,
+    /// The flag before the change value is neither 0 nor 1.
+    InvalidChangeFlag,
+}
+
+impl SelectionOutput {
+    /// Map the indices of a selection made on a filtered copy of the inputs back to the original
+    /// inputs, where `original[i]` is the original index of the `i`th input of the copy.
+    pub(crate) fn remap(&mut self, original: &[InputIndex]) {
+        for index in self.selected_inputs.iter_mut() {
+            *index = original[index.0];
+        }
+        for (index, _) in self.audit.iter_mut().flatten() {
+            *index = original[index.0];
+        }
+    }
+
+    /// Encode the selection in a compact binary format, suitable for caching.
+    ///
+    /// The format is a sequence of unsigned LEB128 varints (7 bits per byte, least significant
+    /// group first, high bit set on every byte but the last):
+    /// `len(selected_inputs)`, then each selected index in order, then the waste zigzag encoded
+    /// as `(waste << 1) ^ (waste >> 63)`, then the position of the chosen algorithm in the
+    /// declaration order of [`Algorithm`], then 0 without change or 1 followed by the change value.
+    /// The audit trail and the iterations are not part of the format.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_varint(&mut bytes, self.selected_inputs.len() as u64);
+        for &index in &self.selected_inputs {
+            write_varint(&mut bytes, index.0 as u64);
+        }
+        write_varint(
+            &mut bytes,
+            ((self.waste.0 << 1) ^ (self.waste.0 >> 63)) as u64,
+        );
+        let algorithm = Algorithm::ALL
+            .iter()
+            .position(|&algorithm| algorithm == self.chosen_algorithm)
+            .expect("every algorithm is listed");
+        write_varint(&mut bytes, algorithm as u64);
+        match self.change_value {
+            Some(change_value) => {
+                write_varint(&mut bytes, 1);
+                write_varint(&mut bytes, change_value);
+            }
+            None => write_varint(&mut bytes, 0),
+        }
+        bytes
+    }
+
+    /// Decode a selection produced by [`SelectionOutput::encode`].
+    pub fn decode(bytes: &[u8]) -> Result<SelectionOutput, DecodeError> {
+        let mut cursor = bytes;
+        let count = read_varint(&mut cursor)?;
+        let mut selected_inputs = Vec::new();
+        for _ in 0..count {
+            let index = read_varint(&mut cursor)?;
+            let index = usize::try_from(index).map_err(|_| DecodeError::VarIntOverflow)?;
+            selected_inputs.push(InputIndex(index));
+        }
+        let waste = read_varint(&mut cursor)?;
+        let waste = (waste >> 1) as i64 ^ -((waste & 1) as i64);
+        let chosen_algorithm = usize::try_from(read_varint(&mut cursor)?)
+            .ok()
+            .and_then(|algorithm| Algorithm::ALL.get(algorithm).copied())
+            .ok_or(DecodeError::UnknownAlgorithm)?;
+        let change_value = match read_varint(&mut cursor)? {
+            0 => None,
+            1 => Some(read_varint(&mut cursor)?),
+            _ => return Err(DecodeError::InvalidChangeFlag),
+        };
+        if !cursor.is_empty() {
+            return Err(DecodeError::TrailingBytes);
+        }
+        Ok(SelectionOutput {
+            selected_inputs,
+            waste: WasteMetric(waste),
+            audit: None,
+            chosen_algorithm,
+            change_value,
+            iterations: 0,
+        })
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_varint(cursor: &mut &[u8]) -> Result<u64, DecodeError> {
+    let mut value: u64 = 0;
+    let mut shift = 0;
+    loop {
+        let (&byte, rest) = cursor.split_first().ok_or(DecodeError::UnexpectedEnd)?;
+        *cursor = rest;
+        // The tenth byte may only carry the single remaining bit of a u64.
+        if shift == 63 && byte > 1 {
+            return Err(DecodeError::VarIntOverflow);
+        }
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
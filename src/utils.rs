@@ -0,0 +1,845 @@
+//! Fee and waste arithmetic shared by the algorithms, and analytics over their results.
+
+use alloc::{vec, vec::Vec};
+
+use crate::{
+    algorithms::lowestlarger::select_coin_lowestlarger,
+    types::{
+        Algorithm, Amount, CoinSelectionOpt, DustError, ExcessStrategy, FeeRate, InputIndex,
+        OutputGroup, SelectionError, SelectionOutput, SelectionReason, SubDustPolicy, WasteMetric,
+        WasteModel,
+    },
+};
+
+/// Perform Coinselection with `select` on `(value, weight, is_segwit)` inputs and a target
+/// denominated in the [`Amount`] `A`, while `options` stay in sats and sats per 1000 weight units.
+/// The selection runs in `A`'s base units, so fees are not rounded to whole sats; the waste in
+/// the result is in base units too. `options.target_value` is ignored in favour of `target`.
+/// Indices in the result refer to `inputs`.
+pub fn select_coin_in<A: Amount, F>(
+    inputs: &[(A, u32, bool)],
+    target: A,
+    options: CoinSelectionOpt,
+    select: F,
+) -> Result<SelectionOutput, SelectionError>
+where
+    F: FnOnce(&[OutputGroup], CoinSelectionOpt) -> Result<SelectionOutput, SelectionError>,
+{
+    let to_units = |sats: u64| {
+        sats.checked_mul(A::UNITS_PER_SAT)
+            .ok_or(SelectionError::Overflow)
+    };
+    let scale_feerate =
+        |feerate: FeeRate| to_units(feerate.as_sat_per_kwu()).map(FeeRate::from_sat_per_kwu);
+    let groups: Vec<OutputGroup> = inputs
+        .iter()
+        .map(|&(value, weight, is_segwit)| OutputGroup::single(value.to_units(), weight, is_segwit))
+        .collect();
+    let options = CoinSelectionOpt {
+        target_value: target.to_units(),
+        target_feerate: scale_feerate(options.target_feerate)?,
+        long_term_feerate: options.long_term_feerate.map(scale_feerate).transpose()?,
+        min_absolute_fee: to_units(options.min_absolute_fee)?,
+        drain_cost: to_units(options.drain_cost)?,
+        cost_per_input: to_units(options.cost_per_input)?,
+        cost_per_output: to_units(options.cost_per_output)?,
+        min_drain_value: to_units(options.min_drain_value)?,
+        dust_threshold: to_units(options.dust_threshold)?,
+        max_excess_to_fee: options.max_excess_to_fee.map(to_units).transpose()?,
+        anchor: options
+            .anchor
+            .map(|(value, weight)| to_units(value).map(|value| (value, weight)))
+            .transpose()?,
+        ..options
+    };
+    select(&groups, options)
+}
+
+/// Map each of the caller's `rows` (e.g. wallet database records) to an [`OutputGroup`] with `f`.
+/// The returned groups keep the order of `rows`, so selected indices refer back to it.
+pub fn output_groups_from<T, F: Fn(&T) -> OutputGroup>(rows: &[T], f: F) -> Vec<OutputGroup> {
+    rows.iter().map(f).collect()
+}
+
+/// Weights of the factors combined by [`quality_score`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QualityWeights {
+    /// Weight of the waste, normalized by the target value.
+    pub waste: f64,
+    /// Weight of the number of selected inputs.
+    pub input_count: f64,
+    /// Weight of the privacy proxy: the number of distinct values among the selected inputs.
+    pub privacy: f64,
+}
+
+/// Score a selection holistically, lower being better, to rank candidate solutions found by
+/// different algorithms:
+///
+/// ```text
+/// waste * (waste / target_value)
+///     + input_count * sum(selected_inputs.input_count)
+///     - privacy * distinct_values(selected_inputs)
+/// ```
+pub fn quality_score(
+    output: &SelectionOutput,
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+    weights: QualityWeights,
+) -> f64 {
+    let normalized_waste = output.waste.0 as f64 / options.target_value.max(1) as f64;
+    let mut values: Vec<u64> = output
+        .selected_inputs
+        .iter()
+        .map(|&index| inputs[index].value)
+        .collect();
+    values.sort_unstable();
+    values.dedup();
+    let input_count = total_input_count(inputs, &output.selected_inputs);
+    weights.waste * normalized_waste + weights.input_count * input_count as f64
+        - weights.privacy * values.len() as f64
+}
+
+/// The waste of spending the `selected` inputs, computed as the selectors do, so a selection
+/// made elsewhere can be compared with theirs. With `fee` the fee for the selected weight plus
+/// `base_weight` at `target_feerate`, at least `min_absolute_fee`:
+///
+/// ```text
+/// waste = (fee - long_term_fee) + drain_cost    with a drain output
+/// waste = (fee - long_term_fee)                 while the excess goes to the recipient
+/// waste = (fee - long_term_fee) + excess        otherwise, excess = value - target_value - fee
+/// ```
+///
+/// where `long_term_fee` is the fee for the same weight at `long_term_feerate`, zero without
+/// one. While the excess goes to a drain, the selection has a drain output if its change reaches
+/// `min_drain_value`, as for the selectors that create change; a smaller excess is paid as fee. [`WasteModel::Legacy`] charges `n * long_term_fee` for `n` inputs instead and clamps the
+/// timing term at zero. The anchor, if any, counts towards the value and weight. Each input of a
+/// group past its first adds [`CoinSelectionOpt::cost_per_input`].
+/// Saturates at `i64::MAX`, if an amount overflows.
+pub fn compute_waste<I: Copy + Into<usize>>(
+    inputs: &[OutputGroup],
+    selected: &[I],
+    options: &CoinSelectionOpt,
+) -> i64 {
+    let Ok((value, weight)) = selection_totals(inputs, selected, options) else {
+        return i64::MAX;
+    };
+    let creates_drain = drains_excess(options)
+        && drain_change(options, value, weight)
+            .is_ok_and(|change| change > 0 && change >= options.min_drain_value);
+    selection_waste(inputs, selected, options, value, weight, creates_drain).unwrap_or(i64::MAX)
+}
+
+/// The feerate a transaction spending the selection pays, rounded down to the sat/kWU.
+///
+/// `include_drain_weight` tells whether the transaction has a drain (change) output. A changeless
+/// transaction pays its whole excess over the target as fee, over `base_weight` plus the inputs.
+/// With a drain, the drain output takes everything above the fee for the full weight at
+/// [`CoinSelectionOpt::target_feerate`], and `drain_weight` counts towards the denominator.
+pub fn fee_rate_achieved(
+    output: &SelectionOutput,
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+    include_drain_weight: bool,
+) -> FeeRate {
+    let (value, inputs_weight) =
+        selection_totals(inputs, &output.selected_inputs, options).unwrap_or((u64::MAX, u32::MAX));
+    let mut weight = options.base_weight.saturating_add(inputs_weight);
+    let fee = if include_drain_weight {
+        weight = weight.saturating_add(options.drain_weight);
+        calculate_fee(weight, options.target_feerate)
+            .max(options.min_absolute_fee)
+            .min(value.saturating_sub(options.target_value))
+    } else {
+        value.saturating_sub(options.target_value)
+    };
+    FeeRate::from_sat_per_kwu(
+        u64::try_from(u128::from(fee) * 1000 / u128::from(weight.max(1))).unwrap_or(u64::MAX),
+    )
+}
+
+/// The feerate at which selections `a` and `b` have equal waste, as computed by the crate's
+/// waste metric before the legacy model clamps negative terms.
+///
+/// Under [`WasteModel::Consolidated`] the long-term term of a selection weighing `w` with
+/// `base_weight` is `(feerate - long_term_feerate) * w`, so the break-even feerate is the
+/// `long_term_feerate`.
+/// Under [`WasteModel::Legacy`] the long-term term of a selection with `n` inputs is
+/// `feerate * w - n * long_term_feerate * w`, so the break-even feerate is
+/// `long_term_feerate * (n_a * w_a - n_b * w_b) / (w_a - w_b)`, rounded down to the sat/kWU.
+/// Below it the heavier selection wastes less, above it the lighter one does.
+/// Return None without a `long_term_feerate`, if both selections weigh the same, if the excess
+/// is paid as fee, which makes the waste difference independent of the feerate, or if the
+/// break-even feerate would be negative.
+pub fn break_even_feerate(
+    a: &SelectionOutput,
+    b: &SelectionOutput,
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+) -> Option<FeeRate> {
+    let long_term_feerate = options.long_term_feerate?;
+    if options.excess_strategy != ExcessStrategy::ToDrain || options.max_excess_to_fee.is_some() {
+        return None;
+    }
+    let (_, weight_a) =
+        selection_totals(inputs, &a.selected_inputs, options).unwrap_or((u64::MAX, u32::MAX));
+    let (_, weight_b) =
+        selection_totals(inputs, &b.selected_inputs, options).unwrap_or((u64::MAX, u32::MAX));
+    if weight_a == weight_b {
+        return None;
+    }
+    #[allow(deprecated)]
+    if options.waste_model != WasteModel::Legacy {
+        return Some(long_term_feerate);
+    }
+    let base_weight = i128::from(options.base_weight);
+    let input_weight_a = a.selected_inputs.len() as i128 * (i128::from(weight_a) + base_weight);
+    let input_weight_b = b.selected_inputs.len() as i128 * (i128::from(weight_b) + base_weight);
+    let break_even = i128::from(long_term_feerate.as_sat_per_kwu())
+        * (input_weight_a - input_weight_b)
+        / (i128::from(weight_a) - i128::from(weight_b));
+    u64::try_from(break_even)
+        .ok()
+        .map(FeeRate::from_sat_per_kwu)
+}
+
+/// Estimate the change of a payment of [`CoinSelectionOpt::target_value`] without a full search,
+/// e.g. to preview it while the amount is typed.
+/// This runs the cheap Lowest Larger selection, so the estimate is exact for
+/// [`select_coin_lowestlarger`]; other selectors spend different inputs, and their change may
+/// differ by up to the value of the largest input.
+/// Return None, if the payment cannot be made or leaves no drain output.
+pub fn estimate_change(inputs: &[OutputGroup], options: &CoinSelectionOpt) -> Option<u64> {
+    select_coin_lowestlarger(inputs, *options)
+        .ok()?
+        .change_value
+}
+
+/// The number of `inputs` the selectors leave out as dust, see
+/// [`CoinSelectionOpt::dust_threshold`], e.g. to tell the user how many coins are not worth
+/// spending at the current feerate.
+pub fn dust_input_count(inputs: &[OutputGroup], options: &CoinSelectionOpt) -> usize {
+    inputs
+        .iter()
+        .filter(|input| is_dust(input, options))
+        .count()
+}
+
+/// Split the `selected` inputs into batches, each fitting a transaction of at most
+/// `max_tx_weight` once `base_weight` is added, e.g. to spread a large consolidation over
+/// several standard transactions. The inputs are packed heaviest first into the first batch with
+/// room. An input too heavy to fit even alone gets a batch of its own.
+pub fn partition_selection(
+    selected: &[InputIndex],
+    inputs: &[OutputGroup],
+    max_tx_weight: u32,
+    base_weight: u32,
+) -> Vec<Vec<InputIndex>> {
+    let capacity = max_tx_weight.saturating_sub(base_weight);
+    let mut by_weight = selected.to_vec();
+    by_weight.sort_by_key(|&index| core::cmp::Reverse(inputs[index].weight));
+
+    let mut batches: Vec<(u32, Vec<InputIndex>)> = Vec::new();
+    for index in by_weight {
+        let weight = inputs[index].weight;
+        match batches
+            .iter_mut()
+            .find(|(used, _)| used.saturating_add(weight) <= capacity)
+        {
+            Some((used, batch)) => {
+                *used += weight;
+                batch.push(index);
+            }
+            None => batches.push((weight, vec![index])),
+        }
+    }
+    batches.into_iter().map(|(_, batch)| batch).collect()
+}
+
+/// A target value spending `fraction` of the balance, e.g. `0.9` to "send 90% of balance".
+/// The balance is the total effective value of the economical inputs, less the fee for
+/// `base_weight` and the [`CoinSelectionOpt::cost_per_input`] of every input of a group past its
+/// first. `fraction` is clamped to `(0, 1]`; `1.0` computes the target of a sweep.
+pub fn target_from_fraction(
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+    fraction: f64,
+) -> u64 {
+    let fraction = fraction.clamp(f64::MIN_POSITIVE, 1.0);
+    let balance = inputs
+        .iter()
+        .filter_map(|input| group_effective_value(input, options).ok())
+        .fold(0u64, u64::saturating_add)
+        .saturating_sub(calculate_fee(options.base_weight, options.target_feerate));
+    (balance as f64 * fraction) as u64
+}
+
+/// The effective value of `output` at `feerate`: its value minus the fee to spend it.
+/// Return DustError, if the fee equals or exceeds the value.
+pub fn effective_value_checked(output: &OutputGroup, feerate: FeeRate) -> Result<u64, DustError> {
+    let fee = calculate_fee(output.weight, feerate);
+    match output.value.checked_sub(fee) {
+        Some(effective_value) if effective_value > 0 => Ok(effective_value),
+        _ => Err(DustError {
+            value: output.value,
+            fee,
+        }),
+    }
+}
+
+/// Order candidate selections of `inputs`, best first, the way the combining selectors pick
+/// among their candidates.
+///
+/// Selections order by [`WasteMetric`], and ties are resolved deterministically, in order:
+/// 1. changeless selections first, where the excess would otherwise go to a drain output;
+/// 2. with [`CoinSelectionOpt::prefer_clusters`], more inputs spent in whole clusters;
+/// 3. fewer inputs, counting every input of a group;
+/// 4. the lowest sum of selected indices;
+/// 5. [`SelectionOutput::cmp_by_waste`].
+pub fn compare_selections(
+    inputs: &[OutputGroup],
+    a: &SelectionOutput,
+    b: &SelectionOutput,
+    options: &CoinSelectionOpt,
+) -> core::cmp::Ordering {
+    // Only changeless selections avoid a drain output when the excess would otherwise go to one.
+    let creates_change =
+        |selection: &SelectionOutput| drains_excess(options) && selection.change_value.is_some();
+    let clustered_inputs = |selection: &SelectionOutput| -> usize {
+        if !options.prefer_clusters {
+            return 0;
+        }
+        selection
+            .selected_inputs
+            .iter()
+            .map(|&index| inputs[index].input_count)
+            .filter(|&input_count| input_count > 1)
+            .sum()
+    };
+    let rank = |selection: &SelectionOutput| {
+        (
+            selection.waste.0,
+            creates_change(selection),
+            core::cmp::Reverse(clustered_inputs(selection)),
+            total_input_count(inputs, &selection.selected_inputs),
+            selection
+                .selected_inputs
+                .iter()
+                .map(|index| index.0)
+                .sum::<usize>(),
+        )
+    };
+    rank(a).cmp(&rank(b)).then_with(|| a.cmp_by_waste(b))
+}
+
+/// The number of inputs the selected groups spend, i.e. the sum of their `input_count`.
+#[inline]
+pub(crate) fn total_input_count<I: Copy + Into<usize>>(
+    inputs: &[OutputGroup],
+    selected_inputs: &[I],
+) -> usize {
+    selected_inputs
+        .iter()
+        .map(|&index| inputs[index.into()].input_count)
+        .sum()
+}
+
+/// Accumulated value and weight of the selected inputs, including the anchor.
+/// Return Overflow, if either does not fit.
+#[inline]
+pub(crate) fn selection_totals<I: Copy + Into<usize>>(
+    inputs: &[OutputGroup],
+    selected_inputs: &[I],
+    options: &CoinSelectionOpt,
+) -> Result<(u64, u32), SelectionError> {
+    let mut has_segwit = false;
+    selected_inputs
+        .iter()
+        .try_fold(options.anchor.unwrap_or((0, 0)), |totals, &index| {
+            accumulate(totals, &inputs[index.into()], &mut has_segwit)
+        })
+}
+
+/// The weight of the segwit marker and flag bytes, paid once by a transaction spending any
+/// segwit input.
+pub(crate) const SEGWIT_MARKER_WEIGHT: u32 = 2;
+
+/// Add `input` to the accumulated value and weight, and the [`SEGWIT_MARKER_WEIGHT`] with the
+/// first segwit input. `has_segwit` tells whether one was accumulated already.
+/// Return Overflow, if either does not fit.
+#[inline]
+pub(crate) fn accumulate(
+    (accumulated_value, accumulated_weight): (u64, u32),
+    input: &OutputGroup,
+    has_segwit: &mut bool,
+) -> Result<(u64, u32), SelectionError> {
+    let marker_weight = if input.is_segwit && !*has_segwit {
+        SEGWIT_MARKER_WEIGHT
+    } else {
+        0
+    };
+    *has_segwit |= input.is_segwit;
+    Ok((
+        accumulated_value
+            .checked_add(input.value)
+            .ok_or(SelectionError::Overflow)?,
+        accumulated_weight
+            .checked_add(input.weight)
+            .and_then(|weight| weight.checked_add(marker_weight))
+            .ok_or(SelectionError::Overflow)?,
+    ))
+}
+
+/// The value a selection paying `estimated_fee` must reach: the target, the required change and
+/// the buffered fee, at least `min_absolute_fee`.
+/// Return Overflow, if it does not fit in a `u64`.
+#[inline]
+pub(crate) fn required_value(
+    options: &CoinSelectionOpt,
+    estimated_fee: u64,
+) -> Result<u64, SelectionError> {
+    let fee = buffered_fee(options, estimated_fee).max(options.min_absolute_fee);
+    options
+        .target_value
+        .checked_add(required_drain_value(options))
+        .and_then(|value| value.checked_add(fee))
+        .ok_or(SelectionError::Overflow)
+}
+
+/// `fee` inflated by [`CoinSelectionOpt::fee_buffer_percent`], rounded up.
+#[inline]
+pub(crate) fn buffered_fee(options: &CoinSelectionOpt, fee: u64) -> u64 {
+    if options.fee_buffer_percent <= 0.0 {
+        return fee;
+    }
+    let buffered = fee as f64 * (1.0 + f64::from(options.fee_buffer_percent) / 100.0);
+    // `f64::ceil` needs `std`; the cast truncates, saturating at `u64::MAX`
+    let truncated = buffered as u64;
+    if (truncated as f64) < buffered {
+        truncated.saturating_add(1)
+    } else {
+        truncated
+    }
+}
+
+/// Whether the accumulated value pays for the target, the fee of the whole transaction and the
+/// required change.
+#[inline]
+pub(crate) fn covers_target(
+    options: &CoinSelectionOpt,
+    accumulated_value: u64,
+    accumulated_weight: u32,
+) -> bool {
+    transaction_fee(options, accumulated_weight)
+        .and_then(|fee| required_value(options, fee))
+        .is_ok_and(|required| accumulated_value >= required)
+}
+
+/// The fee at `target_feerate` of a transaction spending `accumulated_weight` of inputs: with
+/// `base_weight`, and `drain_weight` while the excess goes to a drain output.
+/// Return Overflow, if the weight does not fit in a `u32`.
+#[inline]
+pub(crate) fn transaction_fee(
+    options: &CoinSelectionOpt,
+    accumulated_weight: u32,
+) -> Result<u64, SelectionError> {
+    let drain_weight = if drains_excess(options) {
+        options.drain_weight
+    } else {
+        0
+    };
+    accumulated_weight
+        .checked_add(options.base_weight)
+        .and_then(|weight| weight.checked_add(drain_weight))
+        .map(|weight| calculate_fee(weight, options.target_feerate))
+        .ok_or(SelectionError::Overflow)
+}
+
+/// Build the [`SelectionOutput`] of a selection that covers the target.
+/// A Branch and Bound match is changeless, see [`finalize_changeless`].
+/// Return NoSolutionFound, if the options still reject the selection, see [`check_selection`].
+pub(crate) fn finalize_selection(
+    inputs: &[OutputGroup],
+    selected_inputs: Vec<usize>,
+    options: &CoinSelectionOpt,
+    accumulated_value: u64,
+    accumulated_weight: u32,
+    chosen_algorithm: Algorithm,
+) -> Result<SelectionOutput, SelectionError> {
+    build_selection(
+        inputs,
+        selected_inputs,
+        options,
+        accumulated_value,
+        accumulated_weight,
+        chosen_algorithm,
+        chosen_algorithm == Algorithm::Bnb,
+    )
+}
+
+/// Build the [`SelectionOutput`] of a selection within the Branch and Bound match window, which
+/// creates no change output and pays its excess as fee.
+/// Return NoSolutionFound, if the options still reject the selection, see [`check_selection`].
+pub(crate) fn finalize_changeless(
+    inputs: &[OutputGroup],
+    selected_inputs: Vec<usize>,
+    options: &CoinSelectionOpt,
+    accumulated_value: u64,
+    accumulated_weight: u32,
+    chosen_algorithm: Algorithm,
+) -> Result<SelectionOutput, SelectionError> {
+    build_selection(
+        inputs,
+        selected_inputs,
+        options,
+        accumulated_value,
+        accumulated_weight,
+        chosen_algorithm,
+        true,
+    )
+}
+
+/// The [`SelectionOutput`] of [`finalize_selection`] and [`finalize_changeless`].
+fn build_selection(
+    inputs: &[OutputGroup],
+    selected_inputs: Vec<usize>,
+    options: &CoinSelectionOpt,
+    accumulated_value: u64,
+    accumulated_weight: u32,
+    chosen_algorithm: Algorithm,
+    changeless: bool,
+) -> Result<SelectionOutput, SelectionError> {
+    let (change_value, waste) = check_selection(
+        inputs,
+        &selected_inputs,
+        options,
+        accumulated_value,
+        accumulated_weight,
+        changeless,
+    )?;
+    let selected_inputs: Vec<InputIndex> = selected_inputs.into_iter().map(InputIndex).collect();
+    let audit = options.with_audit.then(|| {
+        selected_inputs
+            .iter()
+            .map(|&index| (index, SelectionReason::NeededToReachTarget))
+            .collect()
+    });
+    Ok(SelectionOutput {
+        selected_inputs,
+        waste: WasteMetric(waste),
+        audit,
+        chosen_algorithm,
+        change_value,
+        iterations: 0,
+    })
+}
+
+/// Apply the options' limits to a selection that covers the target, without building its
+/// [`SelectionOutput`], returning its change value and waste.
+/// The change is what is left once the target and the fee for the whole transaction, drain
+/// output included, are paid; a changeless transaction pays its excess over the fee without the
+/// drain. Both fees are at least `min_absolute_fee`. A `changeless` selection, such as a Branch
+/// and Bound match, never has change.
+/// Return NoSolutionFound or WouldCreateDust, if the options reject the selection, and Overflow,
+/// if its waste does not fit in an `i64`.
+pub(crate) fn check_selection(
+    inputs: &[OutputGroup],
+    selected_inputs: &[usize],
+    options: &CoinSelectionOpt,
+    accumulated_value: u64,
+    accumulated_weight: u32,
+    changeless: bool,
+) -> Result<(Option<u64>, i64), SelectionError> {
+    if options
+        .max_inputs
+        .is_some_and(|max_inputs| total_input_count(inputs, selected_inputs) > max_inputs)
+    {
+        return Err(SelectionError::NoSolutionFound);
+    }
+    let fee = accumulated_weight
+        .checked_add(options.base_weight)
+        .map(|weight| calculate_fee(weight, options.target_feerate).max(options.min_absolute_fee))
+        .ok_or(SelectionError::Overflow)?;
+    let excess = accumulated_value
+        .saturating_sub(options.target_value)
+        .saturating_sub(fee);
+    if let Some(max_excess_to_fee) = options.max_excess_to_fee {
+        if excess > max_excess_to_fee {
+            return Err(SelectionError::NoSolutionFound);
+        }
+    }
+    let change = if drains_excess(options) && !changeless {
+        drain_change(options, accumulated_value, accumulated_weight)?
+    } else {
+        0
+    };
+    let has_change = change > 0;
+    if has_change && change < options.min_drain_value {
+        match options.sub_dust_policy {
+            SubDustPolicy::Reject => return Err(SelectionError::WouldCreateDust),
+            SubDustPolicy::ToFee | SubDustPolicy::ForceMoreInputs => {}
+        }
+    }
+    let paid_as_fee =
+        options.sub_dust_policy == SubDustPolicy::ToFee && change < options.min_drain_value;
+    if let Some(max_change_ratio) = options.max_change_ratio {
+        if has_change
+            && !paid_as_fee
+            && change as f32 > max_change_ratio * options.target_value as f32
+        {
+            return Err(SelectionError::NoSolutionFound);
+        }
+    }
+    let change_value = (has_change && change >= options.min_drain_value).then_some(change);
+    let waste = selection_waste(
+        inputs,
+        selected_inputs,
+        options,
+        accumulated_value,
+        accumulated_weight,
+        change_value.is_some(),
+    )?;
+    Ok((change_value, waste))
+}
+
+/// The change left once the target and the fee for the whole transaction, drain output
+/// included, are paid, zero if the value falls short.
+/// Return Overflow, if the weight does not fit in a `u32`.
+#[inline]
+fn drain_change(
+    options: &CoinSelectionOpt,
+    accumulated_value: u64,
+    accumulated_weight: u32,
+) -> Result<u64, SelectionError> {
+    let fee = accumulated_weight
+        .checked_add(options.base_weight)
+        .and_then(|weight| weight.checked_add(options.drain_weight))
+        .map(|weight| calculate_fee(weight, options.target_feerate).max(options.min_absolute_fee))
+        .ok_or(SelectionError::Overflow)?;
+    Ok(accumulated_value
+        .saturating_sub(options.target_value)
+        .saturating_sub(fee))
+}
+
+/// Waste of a selection: the timing cost of spending the inputs now rather than at the
+/// long-term feerate, plus either the excess paid as fee or the cost of the drain output when
+/// `creates_drain`, combined as [`CoinSelectionOpt::waste_model`] says. An excess going to the
+/// recipient is not lost, so it adds nothing.
+/// Both fees are for the inputs' `accumulated_weight` plus `base_weight`, the fee at
+/// `target_feerate` at least `min_absolute_fee`, so every selector's waste has the same basis.
+/// The drain is counted exactly once, as `drain_cost`: the fee never covers `drain_weight`, so
+/// the fee for it is not added on top. A changeless selection, such as a Branch and Bound match
+/// or change below `min_drain_value` paid as fee under [`SubDustPolicy::ToFee`], counts its
+/// excess, and an excess below zero as none.
+/// The consolidated waste is negative when spending now is cheaper than later by more than the
+/// drain or excess costs, as in Bitcoin Core. The legacy model clamps a negative timing cost and
+/// the total at zero, keeping its original figures.
+/// Return Overflow, if the waste does not fit in an `i64`.
+#[inline]
+pub(crate) fn calculate_waste<I>(
+    selected_inputs: &[I],
+    options: &CoinSelectionOpt,
+    accumulated_value: u64,
+    accumulated_weight: u32,
+    creates_drain: bool,
+) -> Result<i64, SelectionError> {
+    let weight = accumulated_weight
+        .checked_add(options.base_weight)
+        .ok_or(SelectionError::Overflow)?;
+    let estimated_fee = calculate_fee(weight, options.target_feerate).max(options.min_absolute_fee);
+    // Without a drain output, the excess is paid as fee
+    let excess = accumulated_value
+        .saturating_sub(options.target_value)
+        .saturating_sub(estimated_fee);
+    let drain_or_excess = if options.excess_strategy == ExcessStrategy::ToRecipient
+        && options.max_excess_to_fee.is_none()
+    {
+        0
+    } else if creates_drain {
+        options.drain_cost
+    } else {
+        excess
+    };
+
+    #[allow(deprecated)]
+    let waste = match options.waste_model {
+        WasteModel::Consolidated => {
+            let timing_cost = options.long_term_feerate.map_or(0, |long_term_feerate| {
+                i128::from(estimated_fee) - i128::from(calculate_fee(weight, long_term_feerate))
+            });
+            i64::try_from(timing_cost + i128::from(drain_or_excess))
+                .map_err(|_| SelectionError::Overflow)?
+        }
+        WasteModel::Legacy => {
+            let mut waste: u64 = 0;
+            if let Some(long_term_feerate) = options.long_term_feerate {
+                // `n * long_term_feerate * weight`, rounded down so the timing cost rounds up
+                let long_term_fee = selected_inputs.len() as i128
+                    * i128::from(long_term_feerate.as_sat_per_kwu())
+                    * i128::from(weight)
+                    / 1000;
+                let timing_cost = (i128::from(estimated_fee) - long_term_fee).max(0);
+                waste = u64::try_from(timing_cost).map_err(|_| SelectionError::Overflow)?;
+            }
+            waste
+                .checked_add(drain_or_excess)
+                .and_then(|waste| i64::try_from(waste).ok())
+                .ok_or(SelectionError::Overflow)?
+        }
+    };
+    Ok(waste)
+}
+
+/// [`calculate_waste`] plus the [`group_input_cost`] of every selected group, which their
+/// weight leaves out, so a group costs as much as spending its inputs one by one.
+/// Return Overflow, if the waste does not fit in an `i64`.
+fn selection_waste<I: Copy + Into<usize>>(
+    inputs: &[OutputGroup],
+    selected_inputs: &[I],
+    options: &CoinSelectionOpt,
+    accumulated_value: u64,
+    accumulated_weight: u32,
+    creates_drain: bool,
+) -> Result<i64, SelectionError> {
+    let input_cost = selection_input_cost(inputs, selected_inputs, options.cost_per_input);
+    calculate_waste(
+        selected_inputs,
+        options,
+        accumulated_value,
+        accumulated_weight,
+        creates_drain,
+    )?
+    .checked_add(i64::try_from(input_cost).map_err(|_| SelectionError::Overflow)?)
+    .ok_or(SelectionError::Overflow)
+}
+
+/// Effective value of the anchor, zero without one.
+#[inline]
+pub(crate) fn anchor_effective_value(options: &CoinSelectionOpt) -> u64 {
+    let (anchor_value, anchor_weight) = options.anchor.unwrap_or((0, 0));
+    anchor_value.saturating_sub(calculate_fee(anchor_weight, options.target_feerate))
+}
+
+/// Whether the excess of a selection goes to a drain output.
+#[inline]
+fn drains_excess(options: &CoinSelectionOpt) -> bool {
+    options.excess_strategy == ExcessStrategy::ToDrain && options.max_excess_to_fee.is_none()
+}
+
+/// Whether `input` keeps at least [`CoinSelectionOpt::min_efficiency`] of its value once the
+/// fee to spend it is paid.
+#[inline]
+pub(crate) fn meets_min_efficiency(input: &OutputGroup, options: &CoinSelectionOpt) -> bool {
+    options.min_efficiency.is_none_or(|min_efficiency| {
+        input.value > 0
+            && effective_value(input, options) as f32 / input.value as f32 >= min_efficiency
+    })
+}
+
+/// Whether `input` is dust: worth no more than the fee to spend it at `target_feerate`, or than
+/// [`CoinSelectionOpt::dust_threshold`]. The selectors leave dust out.
+#[inline]
+pub(crate) fn is_dust(input: &OutputGroup, options: &CoinSelectionOpt) -> bool {
+    input.value <= options.dust_threshold || !is_economical(input, options)
+}
+
+/// Whether a selector that may create change is allowed to spend `input`.
+#[inline]
+pub(crate) fn spendable_with_change(input: &OutputGroup, options: &CoinSelectionOpt) -> bool {
+    !input.changeless_only || !drains_excess(options)
+}
+
+/// The minimum change a selection must leave room for. None is needed when change is forbidden,
+/// or when the [`SubDustPolicy`] handles a smaller change itself.
+#[inline]
+pub(crate) fn required_drain_value(options: &CoinSelectionOpt) -> u64 {
+    if options.max_excess_to_fee.is_some()
+        || options.sub_dust_policy != SubDustPolicy::ForceMoreInputs
+    {
+        0
+    } else {
+        options.min_drain_value
+    }
+}
+
+#[inline]
+pub(crate) fn calculate_fee(weight: u32, rate: FeeRate) -> u64 {
+    rate.fee_for_weight(weight)
+}
+
+/// Whether selecting `candidate` alongside `selected_inputs` would spend both members of a
+/// forbidden pair.
+#[inline]
+pub(crate) fn is_forbidden(
+    candidate: usize,
+    selected_inputs: &[usize],
+    forbidden_pairs: &[(InputIndex, InputIndex)],
+) -> bool {
+    forbidden_pairs.iter().any(|&(first, second)| {
+        (first.0 == candidate && selected_inputs.contains(&second.0))
+            || (second.0 == candidate && selected_inputs.contains(&first.0))
+    })
+}
+
+/// The [`CoinSelectionOpt::cost_per_input`] of every input of `output` past its first.
+/// The weight of a group leaves out what each further input adds to the transaction, e.g. the
+/// growth of the `vin` count, so a group costs this much more to spend than a single input.
+#[inline]
+pub(crate) fn group_input_cost(output: &OutputGroup, cost_per_input: u64) -> u64 {
+    let extra_inputs = output.input_count.saturating_sub(1);
+    cost_per_input.saturating_mul(u64::try_from(extra_inputs).unwrap_or(u64::MAX))
+}
+
+/// The [`group_input_cost`] of every selected group.
+pub(crate) fn selection_input_cost<I: Copy + Into<usize>>(
+    inputs: &[OutputGroup],
+    selected_inputs: &[I],
+    cost_per_input: u64,
+) -> u64 {
+    selected_inputs
+        .iter()
+        .map(|&index| group_input_cost(&inputs[index.into()], cost_per_input))
+        .fold(0, u64::saturating_add)
+}
+
+/// The effective value of `output` at `feerate`, less its [`group_input_cost`].
+/// Return DustError, if the costs equal or exceed the value.
+pub(crate) fn group_effective_value_at(
+    output: &OutputGroup,
+    feerate: FeeRate,
+    cost_per_input: u64,
+) -> Result<u64, DustError> {
+    let fee = calculate_fee(output.weight, feerate)
+        .saturating_add(group_input_cost(output, cost_per_input));
+    match output.value.checked_sub(fee) {
+        Some(effective_value) if effective_value > 0 => Ok(effective_value),
+        _ => Err(DustError {
+            value: output.value,
+            fee,
+        }),
+    }
+}
+
+/// [`group_effective_value_at`] the options' `target_feerate` and `cost_per_input`.
+#[inline]
+pub(crate) fn group_effective_value(
+    output: &OutputGroup,
+    options: &CoinSelectionOpt,
+) -> Result<u64, DustError> {
+    group_effective_value_at(output, options.target_feerate, options.cost_per_input)
+}
+
+/// Returns the effective value which is the actual value minus the estimated fee of the OutputGroup,
+/// and minus its [`group_input_cost`].
+/// Negative when the costs to spend the OutputGroup exceed its value.
+#[inline]
+pub(crate) fn effective_value(output: &OutputGroup, options: &CoinSelectionOpt) -> i128 {
+    i128::from(output.value)
+        - i128::from(calculate_fee(output.weight, options.target_feerate))
+        - i128::from(group_input_cost(output, options.cost_per_input))
+}
+
+/// Whether `output` is worth more than the costs to spend it at `target_feerate`.
+#[inline]
+pub(crate) fn is_economical(output: &OutputGroup, options: &CoinSelectionOpt) -> bool {
+    effective_value(output, options) > 0
+}
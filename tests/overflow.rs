@@ -0,0 +1,227 @@
+//! Extreme but valid amounts fed to every public entry point: each must return an error
+//! (or a saturated figure, for the analytics helpers) instead of panicking or wrapping.
+
+use rand::{rngs::StdRng, SeedableRng};
+use rust_coinselect::{
+    bnb_solutions, break_even_feerate, changeless_solution_exists, compare_selections,
+    compute_waste, dust_input_count, effective_value_checked, estimate_change, fee_rate_achieved,
+    output_groups_from, partition_selection, quality_score, select_coin, select_coin_anchor_topup,
+    select_coin_bnb, select_coin_bnb_deterministic, select_coin_bnb_with_drain,
+    select_coin_bnb_with_forbidden_pairs, select_coin_bnb_with_preselected, select_coin_coin_age,
+    select_coin_fifo, select_coin_from_pools, select_coin_in, select_coin_knapsack,
+    select_coin_largest_first, select_coin_lowestlarger, select_coin_min_risk, select_coin_optimal,
+    select_coin_rbf, select_coin_recent_change, select_coin_single_lowest_larger,
+    select_coin_smallest_first, select_coin_srd, select_coin_with_confirmed_input,
+    select_coin_with_forced, target_from_fraction, Algorithm, CoinSelectionOpt,
+    CoinSelectionOptBuilder, DecodeError, FeeRate, InputIndex, MatchParameters, OutputGroup,
+    QualityWeights, SelectionError, SelectionOutput, WasteMetric,
+};
+
+fn setup_options(target_value: u64) -> CoinSelectionOpt {
+    CoinSelectionOptBuilder::new(target_value, FeeRate::from_sat_per_kwu(500))
+        .base_weight(10)
+        .drain_weight(50)
+        .drain_cost(10)
+        .cost_per_input(20)
+        .cost_per_output(10)
+        .min_drain_value(500)
+        .build()
+}
+
+fn overflow_cases() -> Vec<(&'static str, Vec<OutputGroup>, CoinSelectionOpt)> {
+    let half = u64::MAX / 2 + 1;
+    let mut cases = Vec::new();
+    cases.push((
+        "values near u64::MAX",
+        OutputGroup::from_utxos(&[(half, 20, false), (half, 20, false)]),
+        setup_options(u64::MAX - 1000),
+    ));
+    cases.push((
+        "weights near u32::MAX",
+        OutputGroup::from_utxos(&[(u64::MAX / 4, u32::MAX - 10, false); 3]),
+        setup_options(u64::MAX / 2),
+    ));
+    let mut options = setup_options(5000);
+    options.target_feerate = FeeRate::from_sat_per_kwu(u64::MAX);
+    cases.push((
+        "max feerate",
+        OutputGroup::from_utxos(&[(6000, 2000, false), (u64::MAX, 2000, false)]),
+        options,
+    ));
+    cases.push((
+        "target of u64::MAX",
+        OutputGroup::from_utxos(&[(6000, 20, false), (u64::MAX, 20, false)]),
+        setup_options(u64::MAX),
+    ));
+    let mut options = setup_options(u64::MAX - 100);
+    options.anchor = Some((u64::MAX - 50, u32::MAX));
+    cases.push((
+        "anchor near the maximums",
+        OutputGroup::from_utxos(&[(6000, 20, false), (u64::MAX, 20, false)]),
+        options,
+    ));
+    let mut options = setup_options(5000);
+    options.min_absolute_fee = u64::MAX;
+    cases.push((
+        "min_absolute_fee of u64::MAX",
+        OutputGroup::from_utxos(&[(6000, 20, false), (u64::MAX, 20, false)]),
+        options,
+    ));
+    let mut options = setup_options(5000);
+    options.drain_cost = u64::MAX;
+    options.long_term_feerate = Some(FeeRate::from_sat_per_kwu(0));
+    cases.push((
+        "drain_cost of u64::MAX",
+        OutputGroup::from_utxos(&[(6000, 20, false), (7000, 20, false)]),
+        options,
+    ));
+    cases
+}
+
+#[test]
+fn test_selectors_reject_overflow() {
+    let mut rng = StdRng::seed_from_u64(229);
+    for (case, inputs, options) in overflow_cases() {
+        let scores = vec![0.5; inputs.len()];
+        let utxos: Vec<(u64, u32, bool)> = inputs
+            .iter()
+            .map(|input| (input.value, input.weight, input.is_segwit))
+            .collect();
+        let results = [
+            ("select_coin", select_coin(&inputs, options, &mut rng)),
+            ("bnb", select_coin_bnb(&inputs, options, &mut rng)),
+            (
+                "bnb_with_forbidden_pairs",
+                select_coin_bnb_with_forbidden_pairs(
+                    &inputs,
+                    options,
+                    &[(InputIndex(0), InputIndex(1))],
+                    &mut rng,
+                ),
+            ),
+            (
+                "bnb_with_drain",
+                select_coin_bnb_with_drain(&inputs, options, &mut rng),
+            ),
+            (
+                "bnb_with_preselected",
+                select_coin_bnb_with_preselected(&inputs, options, &[InputIndex(0)], &mut rng),
+            ),
+            (
+                "bnb_deterministic",
+                select_coin_bnb_deterministic(&inputs, options),
+            ),
+            ("knapsack", select_coin_knapsack(&inputs, options, &mut rng)),
+            ("lowestlarger", select_coin_lowestlarger(&inputs, options)),
+            (
+                "single_lowest_larger",
+                select_coin_single_lowest_larger(&inputs, options),
+            ),
+            ("largest_first", select_coin_largest_first(&inputs, options)),
+            (
+                "smallest_first",
+                select_coin_smallest_first(&inputs, options),
+            ),
+            ("optimal", select_coin_optimal(&inputs, options)),
+            ("fifo", select_coin_fifo(&inputs, options)),
+            ("recent_change", select_coin_recent_change(&inputs, options)),
+            ("anchor_topup", select_coin_anchor_topup(&inputs, options)),
+            ("srd", select_coin_srd(&inputs, options, &mut rng)),
+            ("coin_age", select_coin_coin_age(&inputs, options, &mut rng)),
+            ("min_risk", select_coin_min_risk(&inputs, options, &scores)),
+            ("rbf", select_coin_rbf(&inputs, options, &[InputIndex(0)])),
+            (
+                "with_forced",
+                select_coin_with_forced(&inputs, options, &[InputIndex(0)], select_coin_fifo),
+            ),
+            (
+                "with_confirmed_input",
+                select_coin_with_confirmed_input(&inputs, options, select_coin_fifo),
+            ),
+            (
+                "from_pools",
+                select_coin_from_pools(&inputs, options, select_coin_fifo),
+            ),
+            (
+                "select_coin_in",
+                select_coin_in(&utxos, options.target_value, options, select_coin_fifo),
+            ),
+        ];
+        for (selector, result) in results {
+            assert!(result.is_err(), "{selector} on {case}: {result:?}");
+        }
+        #[cfg(feature = "rayon")]
+        {
+            let result = rust_coinselect::select_coin_bnb_parallel(
+                &inputs,
+                options,
+                2,
+                rust_coinselect::SeedSource::Fixed(229),
+            );
+            assert!(result.is_err(), "bnb_parallel on {case}: {result:?}");
+        }
+        assert_eq!(bnb_solutions(&inputs, options).count(), 0, "{case}");
+        assert!(
+            !changeless_solution_exists(&inputs, options, &mut rng),
+            "{case}"
+        );
+    }
+}
+
+#[test]
+fn test_analytics_saturate_on_overflow() {
+    for (_, inputs, options) in overflow_cases() {
+        let everything = SelectionOutput {
+            selected_inputs: (0..inputs.len()).map(InputIndex).collect(),
+            waste: WasteMetric(i64::MAX),
+            audit: None,
+            chosen_algorithm: Algorithm::Srd,
+            change_value: None,
+            iterations: 0,
+        };
+        let weights = QualityWeights {
+            waste: 1.0,
+            input_count: 1.0,
+            privacy: 1.0,
+        };
+        assert!(quality_score(&everything, &inputs, &options, weights).is_finite());
+        fee_rate_achieved(&everything, &inputs, &options, true);
+        fee_rate_achieved(&everything, &inputs, &options, false);
+        break_even_feerate(&everything, &everything, &inputs, &options);
+        compare_selections(&inputs, &everything, &everything, &options);
+        compute_waste(&inputs, &everything.selected_inputs, &options);
+        estimate_change(&inputs, &options);
+        dust_input_count(&inputs, &options);
+        target_from_fraction(&inputs, &options, 1.0);
+        partition_selection(&everything.selected_inputs, &inputs, u32::MAX, u32::MAX);
+        output_groups_from(&inputs, |input| *input);
+        for input in &inputs {
+            let _ = effective_value_checked(input, options.target_feerate);
+        }
+        let decoded = SelectionOutput::decode(&everything.encode()).unwrap();
+        assert_eq!(decoded.waste, everything.waste);
+    }
+}
+
+#[test]
+fn test_options_reject_overflow() {
+    let mut options = setup_options(u64::MAX - 10);
+    assert!(matches!(
+        MatchParameters::from_options(&options),
+        Err(SelectionError::Overflow)
+    ));
+    options.target_value = 0;
+    options.cost_per_input = u64::MAX;
+    assert!(matches!(
+        MatchParameters::from_options(&options),
+        Err(SelectionError::Overflow)
+    ));
+    assert_eq!(
+        FeeRate::from_sat_per_kwu(u64::MAX).fee_for_weight(u32::MAX),
+        u64::MAX
+    );
+    assert!(matches!(
+        SelectionOutput::decode(&[0xff; 11]),
+        Err(DecodeError::VarIntOverflow)
+    ));
+}
@@ -0,0 +1,43 @@
+use rust_coinselect::{
+    select_coin_bnb, select_coin_fifo, CoinSelectionOptBuilder, ExcessStrategy, FeeRate,
+    OutputGroup, WasteMetric,
+};
+
+#[test]
+fn test_select_coin_bnb_from_downstream() {
+    let inputs =
+        OutputGroup::from_utxos(&[(3000, 20, false), (7000, 20, false), (2000, 20, false)]);
+    let options = CoinSelectionOptBuilder::new(9950, FeeRate::from_sat_per_kwu(500))
+        .base_weight(10)
+        .drain_weight(50)
+        .drain_cost(10)
+        .cost_per_input(20)
+        .cost_per_output(10)
+        .min_drain_value(500)
+        .excess_strategy(ExcessStrategy::ToDrain)
+        .build();
+
+    let selection = select_coin_bnb(&inputs, options, &mut rand::thread_rng()).unwrap();
+    let mut selected = selection.selected_inputs.clone();
+    selected.sort();
+    assert_eq!(selected, vec![0, 1]);
+}
+
+#[test]
+fn test_compare_waste_from_downstream() {
+    let inputs =
+        OutputGroup::from_utxos(&[(3000, 20, false), (7000, 20, false), (2000, 20, false)]);
+    let options = CoinSelectionOptBuilder::new(9950, FeeRate::from_sat_per_kwu(500))
+        .base_weight(10)
+        .drain_weight(50)
+        .drain_cost(10)
+        .cost_per_input(20)
+        .cost_per_output(10)
+        .min_drain_value(500)
+        .build();
+
+    let bnb = select_coin_bnb(&inputs, options, &mut rand::thread_rng()).unwrap();
+    let fifo = select_coin_fifo(&inputs, options).unwrap();
+    assert_eq!(bnb.waste.cmp(&fifo.waste), bnb.waste.0.cmp(&fifo.waste.0));
+    assert!(WasteMetric(10) < WasteMetric(20));
+}